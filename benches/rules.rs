@@ -0,0 +1,55 @@
+//! Compares the table-driven `LifelikeRule` with the hardcoded `ConwayRule` on a
+//! dense board, to check that generic rules don't slow the simulation down.
+//!
+//! Run with `cargo bench`. Uses only the standard library, timing the fastest of
+//! several runs of each.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use conway_game_of_rust::grid::Grid;
+use conway_game_of_rust::rule::{ConwayRule, LifelikeRule, Rule};
+
+const SIZE: usize = 512;
+const GENERATIONS: usize = 20;
+const RUNS: usize = 5;
+
+/// The fastest time to run `GENERATIONS` generations of the same soup under `rule`.
+fn time_rule(rule: impl Rule + Clone + 'static) -> Duration {
+    let mut soup = Grid::new(SIZE, SIZE);
+    soup.randomize(0.5, 1);
+    soup.set_rule(rule);
+    (0..RUNS)
+        .map(|_| {
+            let mut grid = soup.clone();
+            let start = Instant::now();
+            for _ in 0..GENERATIONS {
+                grid.next_generation();
+            }
+            black_box(grid.population());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let conway = time_rule(ConwayRule);
+    let table: LifelikeRule = "B3/S23".parse().expect("valid rulestring");
+    let table = time_rule(table);
+
+    let per_generation = |total: Duration| total / GENERATIONS as u32;
+    println!("{SIZE}x{SIZE} soup, {GENERATIONS} generations, best of {RUNS}:");
+    println!(
+        "  ConwayRule (match):   {:?}/generation",
+        per_generation(conway)
+    );
+    println!(
+        "  LifelikeRule (table): {:?}/generation",
+        per_generation(table)
+    );
+    println!(
+        "  table / match: {:.2}",
+        table.as_secs_f64() / conway.as_secs_f64()
+    );
+}
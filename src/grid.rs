@@ -1,8 +1,17 @@
 use core::panic;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::plaintext::ParseError;
+use crate::rule::{ConwayRule, LifelikeRule, Rule};
+use crate::storage::CellStore;
 
 /// An enum that represents the state of an individual cell.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CellState {
     Alive,
     Dead,
@@ -18,18 +27,451 @@ impl CellState {
     }
 }
 
+/// Default upper bound on `width * height` accepted by `Grid::try_new`.
+/// At one byte per cell this caps a single grid at 256 MiB.
+pub const MAX_CELLS: usize = 1 << 28;
+
+/// Number of past generations kept for `Grid::detect_cycle`, which bounds the longest
+/// period it can find.
+pub const CYCLE_HISTORY_LEN: usize = 64;
+
+/// Most memory the past generations kept for `Grid::detect_cycle` may take. Boards
+/// too large to keep `CYCLE_HISTORY_LEN` of them within it keep fewer (but at least
+/// one), finding only shorter periods.
+pub const CYCLE_HISTORY_BYTES: usize = 64 << 20;
+
+/// Errors returned when a grid can't be created with the requested dimensions.
+#[derive(Debug, PartialEq)]
+pub enum GridError {
+    /// `width * height` doesn't fit in a `usize`.
+    Overflow { width: usize, height: usize },
+    /// `width * height` exceeds the allowed maximum number of cells.
+    TooLarge {
+        width: usize,
+        height: usize,
+        max_cells: usize,
+    },
+    /// The number of cells supplied (e.g. when deserializing) isn't `width * height`.
+    CellCount {
+        width: usize,
+        height: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Overflow { width, height } => {
+                write!(f, "grid dimensions {width}x{height} overflow usize")
+            }
+            GridError::TooLarge {
+                width,
+                height,
+                max_cells,
+            } => write!(
+                f,
+                "grid dimensions {width}x{height} exceed the maximum of {max_cells} cells"
+            ),
+            GridError::CellCount {
+                width,
+                height,
+                found,
+            } => write!(
+                f,
+                "expected {} cells for a {width}x{height} grid, found {found}",
+                width * height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// How `count_live_neighbors` treats neighbors that fall off the edge of the grid.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryMode {
+    /// Cells beyond the border are always dead (the classic bounded board).
+    #[default]
+    Dead,
+    /// Cells beyond the border are always alive.
+    Alive,
+    /// Cells beyond the border reflect the nearest in-bounds cell, so an edge
+    /// row or column acts as its own neighbor.
+    Mirror,
+    /// Coordinates wrap around to the opposite edge, making the board a torus,
+    /// so a glider leaving the right edge reappears on the left.
+    Wrap,
+}
+
+/// "bounded", "alive border", "mirrored" or "torus".
+impl fmt::Display for BoundaryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BoundaryMode::Dead => "bounded",
+            BoundaryMode::Alive => "alive border",
+            BoundaryMode::Mirror => "mirrored",
+            BoundaryMode::Wrap => "torus",
+        })
+    }
+}
+
+/// How a grid keeps its cells, chosen with `Grid::set_backend`. Either gives the same
+/// results; they differ in what they cost.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Backend {
+    /// A flat buffer of every cell (bit-packed with the `bitpack` feature). Fast to
+    /// step however crowded the board, but costs memory and time for the whole area.
+    #[default]
+    Dense,
+    /// Only the live cells' positions, with just the cells around them visited each
+    /// generation. Cheap for a few patterns on a big board, slow for a crowded one.
+    Sparse,
+}
+
+/// "dense" or "sparse".
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backend::Dense => "dense",
+            Backend::Sparse => "sparse",
+        })
+    }
+}
+
+/// Which surrounding cells `count_live_neighbors` counts as neighbors.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Neighborhood {
+    /// All 8 surrounding cells, orthogonal and diagonal (the classic choice).
+    #[default]
+    Moore,
+    /// Only the 4 orthogonal cells: up, down, left and right.
+    VonNeumann,
+}
+
+impl Neighborhood {
+    /// The (row, col) offsets of the neighbors relative to a cell.
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Moore => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Neighborhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        }
+    }
+}
+
+/// "Moore" or "von Neumann".
+impl fmt::Display for Neighborhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Neighborhood::Moore => "Moore",
+            Neighborhood::VonNeumann => "von Neumann",
+        })
+    }
+}
+
+/// The settings a grid evolves under: the rule deciding births and deaths, what lies
+/// beyond the border and which surrounding cells are neighbors. The default is
+/// Conway's rule on a bounded board with the Moore neighborhood.
+///
+/// With the `serde` feature, the boundary mode and neighborhood are serialized along
+/// with a grid's cells; the rule is not.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Universe {
+    /// Decides each cell's next state; shared so cloning a grid stays cheap.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rule: Arc<dyn Rule>,
+    pub boundary_mode: BoundaryMode,
+    pub neighborhood: Neighborhood,
+}
+
+impl Default for Universe {
+    fn default() -> Self {
+        Universe {
+            rule: Arc::new(ConwayRule),
+            boundary_mode: BoundaryMode::default(),
+            neighborhood: Neighborhood::default(),
+        }
+    }
+}
+
+/// For example "B3/S23, torus, Moore", with the rule written as a rulestring.
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {}",
+            LifelikeRule::from_rule(self.rule.as_ref()),
+            self.boundary_mode,
+            self.neighborhood
+        )
+    }
+}
+
+/// A quarter turn or mirror image, applied to the whole board by `Grid::transform` or
+/// to part of it by `Grid::transform_region`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Transform {
+    /// A quarter turn clockwise.
+    RotateCw,
+    /// A quarter turn counterclockwise.
+    RotateCcw,
+    /// Mirrors left to right.
+    FlipHorizontal,
+    /// Mirrors top to bottom.
+    FlipVertical,
+}
+
+impl Transform {
+    /// Where the cell at (row, col) of a `height` x `width` block ends up.
+    fn apply(self, row: usize, col: usize, height: usize, width: usize) -> (usize, usize) {
+        match self {
+            Transform::RotateCw => (col, height - 1 - row),
+            Transform::RotateCcw => (width - 1 - col, row),
+            Transform::FlipHorizontal => (row, width - 1 - col),
+            Transform::FlipVertical => (height - 1 - row, col),
+        }
+    }
+
+    /// True for the rotations, which turn a `height` x `width` block into a
+    /// `width` x `height` one.
+    fn swaps_axes(self) -> bool {
+        matches!(self, Transform::RotateCw | Transform::RotateCcw)
+    }
+}
+
 /// A struct which holds the data for the grid.
 ///
 /// IMPLEMENTATION NOTE:
 /// Uses a single flattened `Vec<CellState>` instead of a `Vec<Vec<CellState>>`.
 /// This improves CPU cache locality and performance, as the entire grid is contiguous
 /// in memory. We calculate 2D indices manually using `row * width + col`.
+/// The flat buffer is a `CellStore`, which is bit-packed with the `bitpack` feature.
+///
+/// With the `serde` feature, a grid serializes as its `width`, `height`,
+/// `boundary_mode`, `neighborhood` and `cells`, and deserializing rejects a `cells`
+/// vector whose length isn't `width * height`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridData"))]
 pub struct Grid {
     pub width: usize,
     pub height: usize,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    universe: Universe,
+    cells: CellStore,
+    // XOR of `cell_key(index)` over every live cell, kept up to date on each write
+    // so `state_hash` is O(1).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cell_hash: u64,
+    // Number of live cells, likewise maintained on every write.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    population: usize,
+    // Generations simulated since creation or the last `reset`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    generation: u64,
+    // True when the last `next_generation` left every cell unchanged and nothing
+    // has been edited since.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stable: bool,
+    // The last few generations before the current one, oldest first. Cleared on
+    // edits, since earlier states no longer lead here.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recent_states: VecDeque<PastState>,
+    // The buffer `next_generation` writes the new cells into before swapping it
+    // with `cells`. Kept between calls so steady-state ticks don't allocate.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scratch: CellStore,
+    // Generations each cell has been continuously alive, in row-major order. None
+    // unless age tracking was turned on with `set_age_tracking`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ages: Option<Vec<u32>>,
+    // Taken from `next_revision` whenever the cells change; see `revision`.
+    #[cfg_attr(feature = "serde", serde(skip, default = "next_revision"))]
+    revision: u64,
+}
+
+/// The serialized form of a `Grid`, checked before it becomes one.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridData {
+    width: usize,
+    height: usize,
+    #[serde(default)]
+    boundary_mode: BoundaryMode,
+    #[serde(default)]
+    neighborhood: Neighborhood,
     cells: Vec<CellState>,
 }
 
+#[cfg(feature = "serde")]
+impl TryFrom<GridData> for Grid {
+    type Error = GridError;
+
+    fn try_from(data: GridData) -> Result<Self, Self::Error> {
+        let cell_count = check_dimensions(data.width, data.height, MAX_CELLS)?;
+        if data.cells.len() != cell_count {
+            return Err(GridError::CellCount {
+                width: data.width,
+                height: data.height,
+                found: data.cells.len(),
+            });
+        }
+        let universe = Universe {
+            boundary_mode: data.boundary_mode,
+            neighborhood: data.neighborhood,
+            ..Universe::default()
+        };
+        let mut grid = Grid::with_universe(data.width, data.height, universe);
+        for (index, &state) in data.cells.iter().enumerate() {
+            grid.set(index / data.width, index % data.width, state);
+        }
+        Ok(grid)
+    }
+}
+
+/// A past generation remembered for `Grid::detect_cycle`: its cells, so a matching
+/// `state_hash` can be confirmed cell by cell, and the hash to find candidates fast.
+/// The population does the same for `Grid::detect_spaceship`.
+#[derive(Clone)]
+struct PastState {
+    hash: u64,
+    population: usize,
+    cells: CellStore,
+}
+
+/// A pattern that reappears shifted across the board, found by
+/// `Grid::detect_spaceship`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Spaceship {
+    /// Generations it takes to reappear.
+    pub period: usize,
+    /// Rows down and columns right it moves each period (negative for up and left).
+    pub displacement: (isize, isize),
+}
+
+impl Spaceship {
+    /// "glider" for the one spaceship moving a cell diagonally every 4 generations,
+    /// "spaceship" for any other.
+    pub fn name(&self) -> &'static str {
+        match (self.period, self.displacement) {
+            (4, (-1 | 1, -1 | 1)) => "glider",
+            _ => "spaceship",
+        }
+    }
+}
+
+/// For example "glider: period 4, moves (1,1)".
+impl fmt::Display for Spaceship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (d_row, d_col) = self.displacement;
+        write!(
+            f,
+            "{}: period {}, moves ({d_row},{d_col})",
+            self.name(),
+            self.period
+        )
+    }
+}
+
+/// What a board has settled into, found by `Grid::classify`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Classification {
+    /// No live cells left.
+    Extinct,
+    /// Live cells that no longer change.
+    StillLife,
+    /// Returns to the same state every `period` generations (at least 2).
+    Oscillator { period: usize },
+    /// Returns to the same shape shifted across the board.
+    Spaceship(Spaceship),
+    /// No repeat found among the remembered generations.
+    Chaotic,
+}
+
+/// For example "still life", "period-2 oscillator" or "glider (period 4, 1,1)",
+/// where a spaceship's move is columns right then rows down.
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Classification::Extinct => write!(f, "extinct"),
+            Classification::StillLife => write!(f, "still life"),
+            Classification::Oscillator { period } => write!(f, "period-{period} oscillator"),
+            // Worded as in the title bar, so the two readouts agree
+            Classification::Spaceship(spaceship) => write!(f, "{spaceship}"),
+            Classification::Chaotic => write!(f, "chaotic"),
+        }
+    }
+}
+
+/// A cheap checkpoint of a `Grid`'s cells and dimensions, taken with
+/// `Grid::snapshot` and rolled back to with `Grid::restore`.
+#[derive(Clone, PartialEq)]
+pub struct GridSnapshot {
+    width: usize,
+    height: usize,
+    cells: CellStore,
+    cell_hash: u64,
+    population: usize,
+    generation: u64,
+}
+
+impl GridSnapshot {
+    /// Width of the grid the snapshot was taken from.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the grid the snapshot was taken from.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Heap memory taken by the snapshot's cells, in bytes.
+    pub fn byte_size(&self) -> usize {
+        self.cells.byte_size()
+    }
+}
+
+/// A rectangle of cells, dead ones included, copied out of a grid with
+/// `Grid::copy_block`. It can be stamped back with `Grid::stamp_block` or repeated
+/// across a region with `Grid::tile_region`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Block {
+    width: usize,
+    height: usize,
+    cells: Vec<CellState>, // Row-major
+}
+
+impl Block {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at (`row`, `col`) of the block, or None past its edges.
+    pub fn get(&self, row: usize, col: usize) -> Option<CellState> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        Some(self.cells[row * self.width + col])
+    }
+}
+
 impl Default for Grid {
     fn default() -> Self {
         // Default size suitable for most terminal windows
@@ -40,33 +482,177 @@ impl Default for Grid {
 impl Grid {
     /// Initialize a new Grid of size `width` * `height`, with all values
     /// being `CellState::Dead`.
+    ///
+    /// Panics if the dimensions are rejected by `try_new`; use that instead
+    /// when the size comes from user input.
     pub fn new(width: usize, height: usize) -> Self {
-        let cells = vec![CellState::Dead; width * height];
-        Grid {
+        match Grid::try_new(width, height) {
+            Ok(grid) => grid,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Like `new`, with neighbors beyond the border handled according to `mode`
+    /// instead of the default `BoundaryMode::Dead`.
+    pub fn with_boundary(width: usize, height: usize, mode: BoundaryMode) -> Self {
+        let mut grid = Grid::new(width, height);
+        grid.universe.boundary_mode = mode;
+        grid
+    }
+
+    /// Like `new`, with each generation computed by `rule` instead of `ConwayRule`,
+    /// e.g. `Grid::with_rule(64, 64, "B36/S23".parse::<LifelikeRule>()?)` for HighLife.
+    /// Panics on the same dimensions as `new`.
+    pub fn with_rule(width: usize, height: usize, rule: impl Rule + 'static) -> Self {
+        let mut grid = Grid::new(width, height);
+        grid.set_rule(rule);
+        grid
+    }
+
+    /// Like `new`, with the rule, boundary mode and neighborhood all taken from
+    /// `universe`. Panics on the same dimensions as `new`.
+    pub fn with_universe(width: usize, height: usize, universe: Universe) -> Self {
+        let mut grid = Grid::new(width, height);
+        grid.universe = universe;
+        grid
+    }
+
+    /// Like `new`, but returns an error instead of allocating if `width * height`
+    /// overflows or exceeds `MAX_CELLS`.
+    pub fn try_new(width: usize, height: usize) -> Result<Self, GridError> {
+        Grid::try_new_with_limit(width, height, MAX_CELLS)
+    }
+
+    /// Like `try_new`, with a caller-chosen maximum number of cells.
+    pub fn try_new_with_limit(
+        width: usize,
+        height: usize,
+        max_cells: usize,
+    ) -> Result<Self, GridError> {
+        let cell_count = check_dimensions(width, height, max_cells)?;
+        Ok(Grid {
             width,
             height,
-            cells,
+            universe: Universe::default(),
+            cells: CellStore::new(cell_count),
+            cell_hash: 0,
+            population: 0,
+            generation: 0,
+            stable: false,
+            recent_states: VecDeque::new(),
+            scratch: CellStore::default(),
+            ages: None,
+            revision: next_revision(),
+        })
+    }
+
+    /// Replaces the rule, boundary mode and neighborhood at once. The cells are left
+    /// as they are.
+    pub fn set_universe(&mut self, universe: Universe) {
+        self.universe = universe;
+        self.forget_recent_states();
+    }
+
+    /// The rule, boundary mode and neighborhood the grid evolves under.
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+
+    /// Replaces the rule used by `next_generation`. The default is `ConwayRule`.
+    pub fn set_rule(&mut self, rule: impl Rule + 'static) {
+        self.universe.rule = Arc::new(rule);
+        self.forget_recent_states();
+    }
+
+    /// The rule used by `next_generation`.
+    pub fn rule(&self) -> &dyn Rule {
+        self.universe.rule.as_ref()
+    }
+
+    /// Chooses how neighbors past the border are counted. The default is
+    /// `BoundaryMode::Dead`.
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.universe.boundary_mode = mode;
+        self.forget_recent_states();
+    }
+
+    /// How `count_live_neighbors` treats neighbors past the border.
+    pub fn boundary_mode(&self) -> BoundaryMode {
+        self.universe.boundary_mode
+    }
+
+    /// Chooses which cells `count_live_neighbors` counts. The default is
+    /// `Neighborhood::Moore`.
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.universe.neighborhood = neighborhood;
+        self.forget_recent_states();
+    }
+
+    /// The neighborhood used by `count_live_neighbors`.
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.universe.neighborhood
+    }
+
+    /// Moves the cells to `backend`, which the grid keeps until it's changed again.
+    /// Nothing else changes: the cells, generation and history stay as they are.
+    pub fn set_backend(&mut self, backend: Backend) {
+        if self.backend() != backend {
+            self.cells = self.cells.converted(backend);
+            self.scratch = CellStore::default();
         }
     }
 
+    /// How the grid is keeping its cells.
+    pub fn backend(&self) -> Backend {
+        self.cells.backend()
+    }
+
+    /// Drops what's known about earlier generations after the universe changes, since
+    /// they say nothing about where the new settings will lead.
+    fn forget_recent_states(&mut self) {
+        self.stable = false;
+        self.recent_states.clear();
+    }
+
     /// Returns Some(CellState) if coordinates in bounds, None otherwise.
     pub fn get(&self, row: usize, col: usize) -> Option<&CellState> {
-        let index = self.get_index_from_coords(row, col);
-        self.cells.get(index)
+        let index = self.get_index_from_coords(row, col)?;
+        // Cells may be bit-packed, so hand out references to constants
+        Some(match self.cells.get(index) {
+            CellState::Alive => &CellState::Alive,
+            CellState::Dead => &CellState::Dead,
+        })
+    }
+
+    /// Like `get`, but returns the `CellState` by value since it's `Copy`.
+    pub fn get_copy(&self, row: usize, col: usize) -> Option<CellState> {
+        self.get(row, col).copied()
     }
 
     /// Sets the given coordinate to `new_state`, doing nothing if
     /// coordinates are out of bounds.
     pub fn set(&mut self, row: usize, col: usize, new_state: CellState) {
-        let index = self.get_index_from_coords(row, col);
-        if let Some(cur_state) = self.cells.get_mut(index) {
-            *cur_state = new_state;
+        if let Some(index) = self.get_index_from_coords(row, col) {
+            if self.cells.get(index) != new_state {
+                self.stable = false;
+                self.recent_states.clear();
+                self.revision = next_revision();
+                self.cell_hash ^= cell_key(index);
+                match new_state {
+                    CellState::Alive => self.population += 1,
+                    CellState::Dead => self.population -= 1,
+                }
+                if let Some(ages) = &mut self.ages {
+                    ages[index] = 0;
+                }
+            }
+            self.cells.set(index, new_state);
         }
     }
 
     /// Flips a single cell at (row, col) from Alive->Dead or Dead->Alive.
     pub fn toggle_cell(&mut self, row: usize, col: usize) {
-        if let Some(state) = self.get(row, col) {
+        if let Some(state) = self.get_copy(row, col) {
             self.set(row, col, state.toggle())
         }
     }
@@ -80,141 +666,2056 @@ impl Grid {
         }
     }
 
-    /// Clears the board (sets all cells to Dead).
-    pub fn reset(&mut self) {
-        self.cells = vec![CellState::Dead; self.width * self.height];
+    /// Sets every cell in a rectangular region to `state`. The part of the region
+    /// outside the grid is clipped.
+    pub fn fill_region(
+        &mut self,
+        min_r: usize,
+        max_r: usize,
+        min_c: usize,
+        max_c: usize,
+        state: CellState,
+    ) {
+        let max_r = max_r.min(self.height.saturating_sub(1));
+        let max_c = max_c.min(self.width.saturating_sub(1));
+        for r in min_r..=max_r {
+            for c in min_c..=max_c {
+                self.set(r, c, state);
+            }
+        }
     }
 
-    // fn set_alive(&mut self, coords: &[(usize, usize)]) {
-    //     for &(r, c) in coords {
-    //         self.set(r, c, CellState::Alive);
-    //     }
-    // }
+    /// Sets every cell in `row` to Dead. Does nothing if `row` is out of bounds.
+    pub fn clear_row(&mut self, row: usize) {
+        for c in 0..self.width {
+            self.set(row, c, CellState::Dead);
+        }
+    }
 
-    // fn load_pattern(&mut self, pattern: &str, start_row: usize, start_col: usize) {
-    //     for (row_offset, line) in pattern.trim().lines().enumerate() {
-    //         for (col_offset, ch) in line.trim().chars().enumerate() {
-    //             if ch == '#' {
-    //                 self.set(start_row + row_offset, start_col + col_offset, CellState::Alive);
-    //             }
-    //         }
-    //     }
-    // }
+    /// Sets every cell in `col` to Dead. Does nothing if `col` is out of bounds.
+    pub fn clear_col(&mut self, col: usize) {
+        for r in 0..self.height {
+            self.set(r, col, CellState::Dead);
+        }
+    }
 
-    /// Helper to get the associated 1D index from a 2D `x` and `y` coordinate.
-    fn get_index_from_coords(&self, row: usize, col: usize) -> usize {
-        row * self.width + col
+    /// Sets only the perimeter of a rectangular region to `state`.
+    /// A 1-wide or 1-tall region degrades to a line, and a single cell to a point.
+    pub fn draw_rect_outline(
+        &mut self,
+        min_r: usize,
+        max_r: usize,
+        min_c: usize,
+        max_c: usize,
+        state: CellState,
+    ) {
+        for c in min_c..=max_c {
+            self.set(min_r, c, state);
+            self.set(max_r, c, state);
+        }
+        for r in min_r..=max_r {
+            self.set(r, min_c, state);
+            self.set(r, max_c, state);
+        }
     }
 
-    /// Calculate the next state of the grid.
-    /// 1. Create a new vector buffer.
-    /// 2. Calculate the state for every cell based on neighbors.
-    /// 3. Swap the old vector with the new one.
-    pub fn next_generation(&mut self) {
-        let mut resulting_cells = Vec::with_capacity(self.width * self.height);
-        for row in 0..self.height {
-            for col in 0..self.width {
-                resulting_cells.push(self.find_new_cell_state(row, col));
+    /// Sets every cell on the straight line from (r0, c0) to (r1, c1) to `state`,
+    /// using Bresenham's algorithm. Both endpoints are included and any part of
+    /// the line outside the grid is clipped.
+    pub fn draw_line(&mut self, r0: usize, c0: usize, r1: usize, c1: usize, state: CellState) {
+        let (mut r, mut c) = (r0 as isize, c0 as isize);
+        let (r1, c1) = (r1 as isize, c1 as isize);
+
+        let dr = (r1 - r).abs();
+        let dc = (c1 - c).abs();
+        let step_r = if r < r1 { 1 } else { -1 };
+        let step_c = if c < c1 { 1 } else { -1 };
+
+        // Error term works for both shallow (dc > dr) and steep (dr > dc) slopes
+        let mut err = dc - dr;
+        loop {
+            self.set(r as usize, c as usize, state);
+            if r == r1 && c == c1 {
+                break;
+            }
+            let err2 = err * 2;
+            if err2 > -dr {
+                err -= dr;
+                c += step_c;
+            }
+            if err2 < dc {
+                err += dc;
+                r += step_r;
             }
         }
-
-        self.cells = resulting_cells;
     }
 
-    /// Applies the standard Game of Life rules to a single cell.
-    fn find_new_cell_state(&self, r: usize, c: usize) -> CellState {
-        let cur_state = match self.get(r, c) {
-            Some(state) => state,
-            None => panic!("coordinates out of bounds"),
-        };
+    /// Sets a filled disk of cells centered on `center` (row, col) to `state`,
+    /// using the midpoint circle algorithm. A radius of 0 sets just the center
+    /// cell, and any part of the disk outside the grid is clipped.
+    pub fn draw_disk(&mut self, center: (usize, usize), radius: usize, state: CellState) {
+        let (center_r, center_c) = (center.0 as isize, center.1 as isize);
+        let mut x = radius as isize;
+        let mut y = 0isize;
+        let mut err = 1 - x;
 
-        let live_neighbors = self.count_live_neighbors(r, c);
+        while x >= y {
+            // Each step of the octant yields four horizontal spans of the disk
+            self.fill_row_span(center_r + y, center_c - x, center_c + x, state);
+            self.fill_row_span(center_r - y, center_c - x, center_c + x, state);
+            self.fill_row_span(center_r + x, center_c - y, center_c + y, state);
+            self.fill_row_span(center_r - x, center_c - y, center_c + y, state);
 
-        match cur_state {
-            // Rule 1: Any live cell with 2 or 3 live neighbors lives.
-            // Rule 2: Any live cell with <2 or >3 neighbors dies.
-            CellState::Alive => match live_neighbors {
-                2 | 3 => return CellState::Alive,
-                _ => return CellState::Dead,
-            },
-            // Rule 3: Any dead cell with exactly 3 live neighbors becomes a live cell.
-            CellState::Dead => {
-                if live_neighbors == 3 {
-                    return CellState::Alive;
-                }
-                CellState::Dead
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
             }
         }
     }
 
-    /// Counts how many neighbors of a given cell are alive.
-    /// Checks all 8 surrounding cells.
-    fn count_live_neighbors(&self, row: usize, col: usize) -> usize {
-        let row_i = row as isize;
-        let col_i = col as isize;
+    /// Helper to set the cells of `row` between `from_c` and `to_c` (inclusive),
+    /// clipping any coordinates that fall outside the grid.
+    fn fill_row_span(&mut self, row: isize, from_c: isize, to_c: isize, state: CellState) {
+        if row < 0 || to_c < 0 {
+            return;
+        }
+        for c in from_c.max(0)..=to_c {
+            self.set(row as usize, c as usize, state);
+        }
+    }
 
-        // Relative coordinates for the 8 neighbors
-        const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
+    /// Captures the current cells, dimensions and generation number so they can be
+    /// restored later.
+    pub fn snapshot(&self) -> GridSnapshot {
+        GridSnapshot {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            cell_hash: self.cell_hash,
+            population: self.population,
+            generation: self.generation,
+        }
+    }
 
-        NEIGHBOR_OFFSETS
-            .iter()
-            .filter_map(|&(dr, dc)| {
-                let neighbor_row_i = row_i + dr;
-                let neighbor_col_i = col_i + dc;
+    /// Rolls the board back to `snapshot`. If the snapshot was taken at different
+    /// dimensions, the grid is resized to match it. Settings such as the `universe`
+    /// are left untouched.
+    pub fn restore(&mut self, snapshot: &GridSnapshot) {
+        self.width = snapshot.width;
+        self.height = snapshot.height;
+        // The snapshot may have been taken on the other backend
+        if snapshot.cells.backend() == self.backend() {
+            self.cells.clone_from(&snapshot.cells);
+        } else {
+            self.cells = snapshot.cells.converted(self.backend());
+        }
+        self.cell_hash = snapshot.cell_hash;
+        self.population = snapshot.population;
+        self.generation = snapshot.generation;
+        self.stable = false;
+        self.recent_states.clear();
+        self.reset_ages();
+        self.revision = next_revision();
+    }
 
-                // 1. Boundary check: negative coordinates
-                if neighbor_row_i < 0 || neighbor_col_i < 0 {
-                    return None;
-                }
+    /// Changes the board to `new_width` x `new_height`, anchored at the top-left:
+    /// live cells keep their (row, col) if they still fit and are dropped otherwise,
+    /// and any new area starts Dead. The generation count carries on.
+    ///
+    /// Returns an error, leaving the grid untouched, if the new dimensions would be
+    /// rejected by `try_new`.
+    pub fn resize(&mut self, new_width: usize, new_height: usize) -> Result<(), GridError> {
+        let cell_count = check_dimensions(new_width, new_height, MAX_CELLS)?;
+        let surviving: Vec<(usize, usize)> = self
+            .live_cells()
+            .filter(|&(r, c)| r < new_height && c < new_width)
+            .collect();
+
+        self.width = new_width;
+        self.height = new_height;
+        self.cells = self.cells.emptied(cell_count);
+        self.replace_live_cells(&surviving);
+        Ok(())
+    }
+
+    /// Adds dead rows and columns around the board: `top` rows above, `bottom` below,
+    /// `left` columns to the left and `right` to the right. Live cells move with the
+    /// rows and columns they're in, so the pattern itself is unchanged. The generation
+    /// count carries on.
+    ///
+    /// Returns an error, leaving the grid untouched, if the new dimensions would be
+    /// rejected by `try_new`.
+    pub fn grow(
+        &mut self,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+    ) -> Result<(), GridError> {
+        let new_width = self.width.saturating_add(left).saturating_add(right);
+        let new_height = self.height.saturating_add(top).saturating_add(bottom);
+        let cell_count = check_dimensions(new_width, new_height, MAX_CELLS)?;
+        let moved: Vec<(usize, usize)> = self
+            .live_cells()
+            .map(|(r, c)| (r + top, c + left))
+            .collect();
+
+        self.width = new_width;
+        self.height = new_height;
+        self.cells = self.cells.emptied(cell_count);
+        self.replace_live_cells(&moved);
+        Ok(())
+    }
+
+    /// Moves every live cell `d_row` rows down and `d_col` columns right (negative
+    /// offsets move up and left). Cells pushed off the edge are dropped, unless the
+    /// boundary mode is `Wrap`, in which case they come back in on the opposite side.
+    pub fn translate(&mut self, d_row: isize, d_col: isize) {
+        // Nothing to move, and a 0-wide or 0-high torus has no remainder to wrap by
+        if self.population() == 0 {
+            return;
+        }
+        let (height, width) = (self.height, self.width);
+        let moved: Vec<(usize, usize)> = if self.boundary_mode() == BoundaryMode::Wrap {
+            // Whole laps around the torus change nothing, so only the remainder moves
+            let d_row = d_row.rem_euclid(height as isize) as usize;
+            let d_col = d_col.rem_euclid(width as isize) as usize;
+            self.live_cells()
+                .map(|(r, c)| ((r + d_row) % height, (c + d_col) % width))
+                .collect()
+        } else {
+            // An offset so large the sum overflows certainly pushes the cell off the board
+            self.live_cells()
+                .filter_map(|(r, c)| {
+                    let r = r.checked_add_signed(d_row).filter(|&r| r < height)?;
+                    let c = c.checked_add_signed(d_col).filter(|&c| c < width)?;
+                    Some((r, c))
+                })
+                .collect()
+        };
+        self.cells = self.cells.emptied(self.width * self.height);
+        self.replace_live_cells(&moved);
+    }
+
+    /// Rotates the whole board a quarter turn clockwise. A non-square board swaps its
+    /// width and height.
+    pub fn rotate_cw(&mut self) {
+        self.transform(Transform::RotateCw);
+    }
+
+    /// Rotates the whole board a quarter turn counterclockwise. A non-square board
+    /// swaps its width and height.
+    pub fn rotate_ccw(&mut self) {
+        self.transform(Transform::RotateCcw);
+    }
+
+    /// Mirrors the whole board left to right.
+    pub fn flip_horizontal(&mut self) {
+        self.transform(Transform::FlipHorizontal);
+    }
+
+    /// Mirrors the whole board top to bottom.
+    pub fn flip_vertical(&mut self) {
+        self.transform(Transform::FlipVertical);
+    }
+
+    /// Applies `transform` to the whole board, swapping its width and height for a
+    /// rotation. The generation count carries on.
+    pub fn transform(&mut self, transform: Transform) {
+        let moved: Vec<(usize, usize)> = self
+            .live_cells()
+            .map(|(r, c)| transform.apply(r, c, self.height, self.width))
+            .collect();
+        if transform.swaps_axes() {
+            std::mem::swap(&mut self.width, &mut self.height);
+        }
+        self.cells = self.cells.emptied(self.width * self.height);
+        self.replace_live_cells(&moved);
+    }
+
+    /// Copies the cells of a rectangular region. Any part of it outside the grid is
+    /// copied as dead cells.
+    pub fn copy_block(&self, min_r: usize, max_r: usize, min_c: usize, max_c: usize) -> Block {
+        let cells = (min_r..=max_r)
+            .flat_map(|r| (min_c..=max_c).map(move |c| (r, c)))
+            .map(|(r, c)| self.get_copy(r, c).unwrap_or(CellState::Dead))
+            .collect();
+        Block {
+            width: max_c + 1 - min_c,
+            height: max_r + 1 - min_r,
+            cells,
+        }
+    }
 
-                let (neighbor_row, neighbor_col) =
-                    (neighbor_row_i as usize, neighbor_col_i as usize);
+    /// Writes the cells of `block`, dead ones included, with its top-left corner at
+    /// (`row`, `col`). Whatever would land past the grid's edges is clipped.
+    pub fn stamp_block(&mut self, block: &Block, row: usize, col: usize) {
+        let rows = block.height.min(self.height.saturating_sub(row));
+        let cols = block.width.min(self.width.saturating_sub(col));
+        for dr in 0..rows {
+            for dc in 0..cols {
+                self.set(row + dr, col + dc, block.cells[dr * block.width + dc]);
+            }
+        }
+    }
 
-                // 2. Boundary check: exceeded width/height
-                if neighbor_row >= self.height || neighbor_col >= self.width {
-                    return None;
+    /// Repeats `block` across a rectangular region, starting from its top-left corner,
+    /// and toggles the cells under the block's live cells (an XOR onto what's there).
+    /// Cells under its dead ones are left alone. Where the region isn't a whole number
+    /// of blocks across or down, the last copies are cut off at its right and bottom
+    /// edges, and any of the region outside the grid is clipped.
+    pub fn tile_region(
+        &mut self,
+        min_r: usize,
+        max_r: usize,
+        min_c: usize,
+        max_c: usize,
+        block: &Block,
+    ) {
+        if block.cells.is_empty() {
+            return;
+        }
+        let max_r = max_r.min(self.height.saturating_sub(1));
+        let max_c = max_c.min(self.width.saturating_sub(1));
+        for r in min_r..=max_r {
+            for c in min_c..=max_c {
+                let under = block.get((r - min_r) % block.height, (c - min_c) % block.width);
+                if under == Some(CellState::Alive) {
+                    self.toggle_cell(r, c);
                 }
+            }
+        }
+    }
 
-                Some((neighbor_row, neighbor_col))
-            })
-            // 3. Check if the neighbor is actually alive
-            .filter(|&(neighbor_row, neighbor_col)| {
-                matches!(
-                    self.get(neighbor_row, neighbor_col),
-                    Some(&CellState::Alive)
-                )
+    /// Applies `transform` to the cells of a rectangular region, clipped to the grid.
+    /// The result keeps the region's top-left corner, so a rotated region that isn't
+    /// square sticks out past its old bounds; it's laid over the cells there, and
+    /// anything landing outside the grid is clipped.
+    pub fn transform_region(
+        &mut self,
+        transform: Transform,
+        min_r: usize,
+        max_r: usize,
+        min_c: usize,
+        max_c: usize,
+    ) {
+        let max_r = max_r.min(self.height.saturating_sub(1));
+        let max_c = max_c.min(self.width.saturating_sub(1));
+        if min_r > max_r || min_c > max_c {
+            return;
+        }
+        let (height, width) = (max_r - min_r + 1, max_c - min_c + 1);
+        let moved: Vec<(usize, usize)> = self
+            .live_cells()
+            .filter(|&(r, c)| (min_r..=max_r).contains(&r) && (min_c..=max_c).contains(&c))
+            .map(|(r, c)| {
+                let (r, c) = transform.apply(r - min_r, c - min_c, height, width);
+                (min_r + r, min_c + c)
             })
-            .count()
+            .collect();
+        self.fill_region(min_r, max_r, min_c, max_c, CellState::Dead);
+        self.set_alive(&moved);
     }
-}
 
-/// Allows printing the grid to console/string.
-/// Primarily used for debugging or simple text output, not the main TUI.
-impl fmt::Display for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.cells
-            .chunks_exact(self.width)
-            .map(|row| {
-                row.iter()
-                    .map(|state| match state {
-                        CellState::Alive => '#',
-                        CellState::Dead => '.',
-                    })
-                    .map(|ch| ch.to_string())
-                    .collect::<Vec<String>>()
-                    .join(" ")
-            })
-            .try_for_each(|line| writeln!(f, "{}", line))?;
+    /// Brings a freshly emptied `cells` buffer up to date with the given live cells,
+    /// keeping the generation count. Used by edits that rebuild the whole board.
+    fn replace_live_cells(&mut self, live: &[(usize, usize)]) {
+        self.cell_hash = 0;
+        self.population = 0;
+        self.stable = false;
+        self.recent_states.clear();
+        self.reset_ages();
+        self.revision = next_revision();
+        self.set_alive(live);
+    }
 
-        Ok(())
+    /// Clears the board (sets all cells to Dead) and restarts the generation count.
+    pub fn reset(&mut self) {
+        self.cells = self.cells.emptied(self.width * self.height);
+        self.cell_hash = 0;
+        self.population = 0;
+        self.generation = 0;
+        self.stable = false;
+        self.recent_states.clear();
+        self.reset_ages();
+        self.revision = next_revision();
+    }
+
+    /// Replaces the board with random soup: each cell is Alive with probability
+    /// `density` (clamped to `0.0..=1.0`), so 0.0 clears the board and 1.0 fills it.
+    /// The same `seed` always produces the same board. Restarts the generation count.
+    pub fn randomize(&mut self, density: f64, seed: u64) {
+        self.reset();
+        let density = density.clamp(0.0, 1.0);
+        // Mixed first so nearby seeds start far apart in the sequence rather than
+        // producing the same board shifted by a few cells
+        let start = splitmix64(seed);
+        for index in 0..self.width * self.height {
+            // SplitMix64 over a counter gives an independent 64-bit value per cell;
+            // its top 53 bits become a uniform float in [0, 1)
+            let random = splitmix64(start.wrapping_add((index as u64).wrapping_mul(GOLDEN_GAMMA)));
+            let uniform = (random >> 11) as f64 / (1u64 << 53) as f64;
+            if uniform < density {
+                self.set(index / self.width, index % self.width, CellState::Alive);
+            }
+        }
+    }
+
+    /// Returns a 64-bit hash of the board's dimensions and cells, in O(1).
+    ///
+    /// Equal boards always hash equally, so a mismatch proves two states differ.
+    /// A match is only very likely to mean equality, so callers detecting cycles
+    /// should confirm it by comparing the cells themselves (e.g. via `snapshot`).
+    pub fn state_hash(&self) -> u64 {
+        self.cell_hash ^ splitmix64(((self.width as u64) << 32) ^ self.height as u64)
+    }
+
+    /// A number that changes whenever any cell (or its age) does, and is never
+    /// shared by two grids with different cells, so renderers can cache whatever
+    /// they draw from the cells and redraw only once the revision moves on.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns how many times `next_generation` has run since the grid was created
+    /// or last `reset`.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Turns per-cell age tracking on or off. It's off by default so plain simulations
+    /// don't pay for it; when turned on, every cell starts at age 0.
+    pub fn set_age_tracking(&mut self, enabled: bool) {
+        self.ages = enabled.then(|| vec![0; self.width * self.height]);
+        self.revision = next_revision();
+    }
+
+    /// Whether per-cell ages are being tracked.
+    pub fn age_tracking(&self) -> bool {
+        self.ages.is_some()
+    }
+
+    /// How many generations the cell at (row, col) has been continuously alive: 0 for
+    /// a cell born (or set alive) this generation and for dead cells. Also 0 when age
+    /// tracking is off or the coordinates are out of bounds.
+    pub fn age(&self, row: usize, col: usize) -> u32 {
+        match (&self.ages, self.get_index_from_coords(row, col)) {
+            (Some(ages), Some(index)) => ages[index],
+            _ => 0,
+        }
+    }
+
+    /// Zeroes every tracked age, resized to the current dimensions. Used when the
+    /// whole board is replaced.
+    fn reset_ages(&mut self) {
+        if self.ages.is_some() {
+            self.set_age_tracking(true);
+        }
+    }
+
+    /// True if the most recent `next_generation` changed no cells (a still life or
+    /// an empty board), meaning every later generation will be identical too.
+    /// Any edit to the board clears it.
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    /// Returns the period `p` if the current state also occurred `p` generations ago,
+    /// checking periods from 1 up to `max_period` and returning the smallest match.
+    /// A still life has period 1 and a blinker period 2.
+    ///
+    /// Only the last `CYCLE_HISTORY_LEN` generations since the most recent edit are
+    /// remembered (fewer on boards too big to fit them in `CYCLE_HISTORY_BYTES`).
+    /// Past states are found by `state_hash` and then compared cell by cell, so a
+    /// hash collision is never reported as a cycle.
+    pub fn detect_cycle(&self, max_period: usize) -> Option<usize> {
+        let current = self.state_hash();
+        let count = self.recent_states.len();
+        (1..=max_period.min(count)).find(|&period| {
+            let past = &self.recent_states[count - period];
+            past.hash == current && past.cells == self.cells
+        })
+    }
+
+    /// Returns the period and displacement if the live cells are a shifted copy of the
+    /// live cells `p` generations ago, checking from 1 up to `max_period` and
+    /// returning the smallest match. A board that repeats without moving is a cycle
+    /// (see `detect_cycle`), not a spaceship.
+    ///
+    /// Searches the same remembered generations as `detect_cycle`. Only those with the
+    /// current population are compared, offset by the move of the top-left corner of
+    /// the live cells, so a board with no spaceship costs little to check.
+    pub fn detect_spaceship(&self, max_period: usize) -> Option<Spaceship> {
+        let count = self.recent_states.len();
+        let mut current_corner = None;
+        (1..=max_period.min(count)).find_map(|period| {
+            let past = &self.recent_states[count - period];
+            if past.population != self.population || self.population == 0 {
+                return None;
+            }
+            let current = current_corner
+                .get_or_insert_with(|| top_left(self.cells.alive_indices(), self.width));
+            let (min_r, min_c) = (*current)?;
+            let (past_r, past_c) = top_left(past.cells.alive_indices(), self.width)?;
+            let displacement = (
+                min_r as isize - past_r as isize,
+                min_c as isize - past_c as isize,
+            );
+            if displacement == (0, 0) {
+                return None;
+            }
+            // Same population, so every past cell landing on a live one means the whole
+            // board moved
+            let moved = past.cells.alive_indices().all(|index| {
+                let r = (index / self.width).checked_add_signed(displacement.0);
+                let c = (index % self.width).checked_add_signed(displacement.1);
+                matches!((r, c), (Some(r), Some(c)) if self.get_copy(r, c) == Some(CellState::Alive))
+            });
+            moved.then_some(Spaceship {
+                period,
+                displacement,
+            })
+        })
+    }
+
+    /// How many past generations `detect_cycle` can keep for a board this size.
+    fn cycle_history_len(&self) -> usize {
+        (CYCLE_HISTORY_BYTES / self.cells.byte_size().max(1)).clamp(1, CYCLE_HISTORY_LEN)
+    }
+
+    /// Adds the current state to `recent_states`, recycling the oldest entry's buffer
+    /// once the history is full.
+    fn remember_state(&mut self) {
+        let mut recycled = None;
+        while self.recent_states.len() >= self.cycle_history_len() {
+            recycled = self.recent_states.pop_front();
+        }
+        let mut past = recycled.unwrap_or_else(|| PastState {
+            hash: 0,
+            population: 0,
+            cells: CellStore::default(),
+        });
+        past.hash = self.state_hash();
+        past.population = self.population;
+        past.cells.clone_from(&self.cells);
+        self.recent_states.push_back(past);
+    }
+
+    /// Sums up what the board has settled into, from the population, `is_stable`,
+    /// `detect_cycle` and `detect_spaceship`, looking back up to `CYCLE_HISTORY_LEN`
+    /// generations. Those only see generations run since the last edit, so a board
+    /// that was just edited reads as chaotic until it has been run for a while.
+    pub fn classify(&self) -> Classification {
+        if self.population == 0 {
+            return Classification::Extinct;
+        }
+        if self.stable {
+            return Classification::StillLife;
+        }
+        match self.detect_cycle(CYCLE_HISTORY_LEN) {
+            Some(1) => Classification::StillLife,
+            Some(period) => Classification::Oscillator { period },
+            None => match self.detect_spaceship(CYCLE_HISTORY_LEN) {
+                Some(spaceship) => Classification::Spaceship(spaceship),
+                None => Classification::Chaotic,
+            },
+        }
+    }
+
+    /// Returns the number of live cells on the board, in O(1).
+    pub fn population(&self) -> usize {
+        self.population
+    }
+
+    /// Iterates over the (row, col) of every live cell in row-major order,
+    /// without the caller scanning the dead cells in between.
+    pub fn live_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .alive_indices()
+            .map(|index| (index / self.width, index % self.width))
+    }
+
+    /// Returns the smallest (min_row, max_row, min_col, max_col) rectangle holding
+    /// every live cell, or None if the board is empty.
+    pub fn live_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        // Live cells come out in row-major order, so the rows are the first and last
+        let mut cells = self.live_cells();
+        let (min_r, first_c) = cells.next()?;
+        let (mut max_r, mut min_c, mut max_c) = (min_r, first_c, first_c);
+        for (r, c) in cells {
+            max_r = r;
+            min_c = min_c.min(c);
+            max_c = max_c.max(c);
+        }
+        Some((min_r, max_r, min_c, max_c))
+    }
+
+    /// Returns the number of live cells in the inclusive rectangle
+    /// `min_r..=max_r` x `min_c..=max_c`. Parts of the rectangle outside the
+    /// grid are clipped, so only the cells inside it are scanned.
+    pub fn population_in(&self, min_r: usize, max_r: usize, min_c: usize, max_c: usize) -> usize {
+        if self.width == 0 || self.height == 0 || min_c >= self.width {
+            return 0;
+        }
+        let max_c = max_c.min(self.width - 1);
+        if min_c > max_c {
+            return 0;
+        }
+        (min_r..=max_r.min(self.height - 1))
+            .map(|r| {
+                let row_start = r * self.width;
+                self.cells
+                    .count_alive(row_start + min_c..row_start + max_c + 1)
+            })
+            .sum()
+    }
+
+    /// Returns the fraction of cells that are alive, in the range `0.0..=1.0`.
+    pub fn density(&self) -> f64 {
+        let total = self.width * self.height;
+        if total == 0 {
+            return 0.0;
+        }
+        self.population() as f64 / total as f64
+    }
+
+    /// Shannon entropy (in bits) of the distribution of `block_size` x `block_size`
+    /// cell patterns across the board.
+    ///
+    /// The board is partitioned into non-overlapping blocks starting at the top-left;
+    /// partial blocks along the right/bottom edges are ignored. A uniform board scores
+    /// 0.0, while the maximum possible value is `block_size * block_size` bits.
+    /// `block_size` must be between 1 and 8 so a block fits in a `u64` key.
+    pub fn block_entropy(&self, block_size: usize) -> f64 {
+        assert!(
+            (1..=8).contains(&block_size),
+            "block_size must be between 1 and 8"
+        );
+
+        let mut pattern_counts: HashMap<u64, usize> = HashMap::new();
+        for block_r in (0..self.height / block_size).map(|b| b * block_size) {
+            for block_c in (0..self.width / block_size).map(|b| b * block_size) {
+                // Pack the block's cells into a bitmask, one bit per cell
+                let mut key = 0u64;
+                for r in block_r..block_r + block_size {
+                    for c in block_c..block_c + block_size {
+                        key <<= 1;
+                        if self.get_copy(r, c) == Some(CellState::Alive) {
+                            key |= 1;
+                        }
+                    }
+                }
+                *pattern_counts.entry(key).or_insert(0) += 1;
+            }
+        }
+
+        let total_blocks: usize = pattern_counts.values().sum();
+        if total_blocks == 0 {
+            return 0.0;
+        }
+
+        pattern_counts
+            .values()
+            .map(|&count| {
+                // -p * log2(p), written so a single pattern (p = 1) gives 0.0, not -0.0
+                let p = count as f64 / total_blocks as f64;
+                p * (1.0 / p).log2()
+            })
+            .sum()
+    }
+
+    /// Sets every (row, col) in `coords` to `CellState::Alive`, silently skipping
+    /// coordinates that are out of bounds.
+    pub fn set_alive(&mut self, coords: &[(usize, usize)]) {
+        for &(r, c) in coords {
+            self.set(r, c, CellState::Alive);
+        }
+    }
+
+    /// Sets every (row, col) in `coords` to `CellState::Dead`, silently skipping
+    /// coordinates that are out of bounds.
+    pub fn set_dead(&mut self, coords: &[(usize, usize)]) {
+        for &(r, c) in coords {
+            self.set(r, c, CellState::Dead);
+        }
+    }
+
+    /// Overlays an ASCII pattern onto the grid with its top-left corner at
+    /// (start_row, start_col). `#` cells are set alive; any other character
+    /// (`.` or space) leaves the existing cell untouched. Cells that would land
+    /// outside the grid are clipped.
+    ///
+    /// Every line and character counts, blank or not, so `" #"` sets the cell one
+    /// column to the right of `start_col`.
+    pub fn load_pattern(&mut self, pattern: &str, start_row: usize, start_col: usize) {
+        for (row_offset, line) in pattern.lines().enumerate() {
+            for (col_offset, ch) in line.chars().enumerate() {
+                if ch == '#' {
+                    self.set(
+                        start_row.saturating_add(row_offset),
+                        start_col.saturating_add(col_offset),
+                        CellState::Alive,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Renders the board as a binary (P6) PPM image, drawing each cell as a
+    /// `cell_px` x `cell_px` block: white for alive, black for dead. The bytes can be
+    /// written straight to a `.ppm` file or piped to an image tool.
+    pub fn to_ppm(&self, cell_px: usize) -> Vec<u8> {
+        let image_width = self.width * cell_px;
+        let image_height = self.height * cell_px;
+        let mut ppm = format!("P6\n{image_width} {image_height}\n255\n").into_bytes();
+        ppm.reserve(image_width * image_height * 3);
+
+        for row in 0..self.height {
+            // Build one line of pixels per cell row, then repeat it `cell_px` times
+            let mut pixel_line = Vec::with_capacity(image_width * 3);
+            for state in self.row_states(row) {
+                let value = match state {
+                    CellState::Alive => 255,
+                    CellState::Dead => 0,
+                };
+                pixel_line.extend(std::iter::repeat_n(value, cell_px * 3));
+            }
+            for _ in 0..cell_px {
+                ppm.extend_from_slice(&pixel_line);
+            }
+        }
+        ppm
+    }
+
+    /// Helper to iterate over the cells of `row`, left to right.
+    fn row_states(&self, row: usize) -> impl Iterator<Item = CellState> + '_ {
+        let row_start = row * self.width;
+        (row_start..row_start + self.width).map(|index| self.cells.get(index))
+    }
+
+    /// Helper to get the associated 1D index from a 2D `x` and `y` coordinate.
+    /// Returns None if the coordinate lies outside the grid, so that a column
+    /// past the right edge doesn't silently wrap onto the next row.
+    fn get_index_from_coords(&self, row: usize, col: usize) -> Option<usize> {
+        if row >= self.height || col >= self.width {
+            return None;
+        }
+        Some(row * self.width + col)
+    }
+
+    /// Calculate the next state of the grid.
+    /// 1. Clear the scratch buffer (reusing its allocation).
+    /// 2. Calculate the state for every cell based on neighbors (with the `bitpack`
+    ///    feature, the store does this a word of cells at a time), tallying the new
+    ///    population and cell hash along the way.
+    /// 3. Swap the old vector with the new one, keeping the old one as scratch.
+    ///
+    /// On the sparse backend, step 2 only visits the cells that could be alive next
+    /// generation; see `sparse_step`.
+    pub fn next_generation(&mut self) {
+        self.remember_state();
+        // Taken out of `self` so new cells can be written while neighbors are read
+        let mut resulting_cells = std::mem::take(&mut self.scratch);
+        let (cell_hash, population) = match self.backend() {
+            Backend::Sparse => self.sparse_step(&mut resulting_cells),
+            #[cfg(not(feature = "bitpack"))]
+            Backend::Dense => {
+                let width = self.width;
+                resulting_cells.fill_with(self.width * self.height, |index| {
+                    self.find_new_cell_state(index / width, index % width)
+                })
+            }
+            #[cfg(feature = "bitpack")]
+            Backend::Dense => self.cells.step(
+                &mut resulting_cells,
+                self.width,
+                self.height,
+                self.boundary_mode(),
+                self.neighborhood(),
+                self.rule(),
+            ),
+        };
+
+        self.stable = resulting_cells == self.cells;
+        std::mem::swap(&mut self.cells, &mut resulting_cells);
+        // `resulting_cells` now holds the previous generation: survivors age, and
+        // everything else (births, deaths, dead cells) is back to 0
+        if let Some(ages) = &mut self.ages {
+            for (index, age) in ages.iter_mut().enumerate() {
+                let survived = self.cells.get(index) == CellState::Alive
+                    && resulting_cells.get(index) == CellState::Alive;
+                *age = if survived { age.saturating_add(1) } else { 0 };
+            }
+        }
+        self.scratch = resulting_cells;
+        self.cell_hash = cell_hash;
+        self.population = population;
+        self.generation += 1;
+        self.revision = next_revision();
+    }
+
+    /// Writes the next generation of a sparse board into `out`, as a sparse store, and
+    /// returns its cell hash and population. Only the live cells and the cells around
+    /// them are visited (wrapped onto the board, which covers every boundary mode),
+    /// plus the edges when the border counts as alive, and every cell for a rule that
+    /// brings cells to life with no neighbors at all.
+    fn sparse_step(&self, out: &mut CellStore) -> (u64, usize) {
+        let (width, height) = (self.width, self.height);
+        let mut candidates: Vec<usize> =
+            if self.rule().next_state(CellState::Dead, 0) == CellState::Alive {
+                (0..width * height).collect()
+            } else {
+                let mut candidates = Vec::with_capacity(self.population * 9);
+                for (r, c) in self.live_cells() {
+                    for dr in -1..=1 {
+                        for dc in -1..=1 {
+                            let r = (r as isize + dr).rem_euclid(height as isize) as usize;
+                            let c = (c as isize + dc).rem_euclid(width as isize) as usize;
+                            candidates.push(r * width + c);
+                        }
+                    }
+                }
+                // An empty board has no edges to visit
+                if self.boundary_mode() == BoundaryMode::Alive && width > 0 && height > 0 {
+                    for c in 0..width {
+                        candidates.extend([c, (height - 1) * width + c]);
+                    }
+                    for r in 0..height {
+                        candidates.extend([r * width, r * width + width - 1]);
+                    }
+                }
+                candidates
+            };
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        out.clear_sparse(width * height);
+        let mut tally = (0, 0);
+        for index in candidates {
+            if self.find_new_cell_state(index / width, index % width) == CellState::Alive {
+                out.set(index, CellState::Alive);
+                tally = (tally.0 ^ cell_key(index), tally.1 + 1);
+            }
+        }
+        tally
+    }
+
+    /// Advances `n` generations. Once the board is stable the rest are skipped rather
+    /// than computed, since they'd leave every cell as it is; only the generation
+    /// count moves on. (Not with age tracking on, where each one ages the cells.)
+    pub fn step_n(&mut self, n: u64) {
+        for done in 0..n {
+            if self.stable && self.ages.is_none() {
+                self.generation += n - done;
+                return;
+            }
+            self.next_generation();
+        }
+    }
+
+    /// Applies the grid's rule to a single cell.
+    fn find_new_cell_state(&self, r: usize, c: usize) -> CellState {
+        let cur_state = match self.get_copy(r, c) {
+            Some(state) => state,
+            None => panic!("coordinates out of bounds"),
+        };
+
+        let live_neighbors = self.count_live_neighbors(r, c);
+        self.universe.rule.next_state(cur_state, live_neighbors)
+    }
+
+    /// Returns the live-neighbor count of every cell, in row-major order
+    /// (index `row * width + col`). Computed in a single pass so callers such as
+    /// renderers can cache the result instead of re-counting cell by cell.
+    pub fn neighbor_counts(&self) -> Vec<usize> {
+        let mut counts = Vec::with_capacity(self.width * self.height);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                counts.push(self.count_live_neighbors(row, col));
+            }
+        }
+        counts
+    }
+
+    /// Returns the state every cell will have after the next `next_generation`, in
+    /// row-major order, without advancing the grid. Renderers can compare it with the
+    /// current cells to show which are about to be born or die.
+    pub fn preview_next(&self) -> Vec<CellState> {
+        (0..self.width * self.height)
+            .map(|index| self.find_new_cell_state(index / self.width, index % self.width))
+            .collect()
+    }
+
+    /// Maps a possibly out-of-bounds neighbor coordinate to the in-bounds cell that
+    /// stands in for it, or None if it is off the edge and the boundary mode has no
+    /// stand-in (Dead / Alive edges).
+    fn resolve_neighbor(&self, row_i: isize, col_i: isize) -> Option<(usize, usize)> {
+        let max_row = self.height as isize - 1;
+        let max_col = self.width as isize - 1;
+
+        // Boundary check: negative coordinates or exceeded width/height
+        let in_bounds = (0..=max_row).contains(&row_i) && (0..=max_col).contains(&col_i);
+        if in_bounds {
+            return Some((row_i as usize, col_i as usize));
+        }
+
+        match self.boundary_mode() {
+            BoundaryMode::Dead | BoundaryMode::Alive => None,
+            BoundaryMode::Mirror => Some((
+                row_i.clamp(0, max_row) as usize,
+                col_i.clamp(0, max_col) as usize,
+            )),
+            BoundaryMode::Wrap => Some((
+                row_i.rem_euclid(self.height as isize) as usize,
+                col_i.rem_euclid(self.width as isize) as usize,
+            )),
+        }
+    }
+
+    /// Counts how many neighbors of a given cell are alive.
+    /// Checks the 8 surrounding cells, or only the 4 orthogonal ones with
+    /// `Neighborhood::VonNeumann`. Neighbors off the edge of the grid are handled
+    /// according to `boundary_mode`: with the default `BoundaryMode::Dead` and Moore
+    /// neighborhood, corner and edge cells have only 3 and 5 candidates.
+    pub fn count_live_neighbors(&self, row: usize, col: usize) -> usize {
+        let row_i = row as isize;
+        let col_i = col as isize;
+
+        self.neighborhood()
+            .offsets()
+            .iter()
+            .filter(|&&(dr, dc)| {
+                // 1. Resolve the neighbor's coordinates according to the boundary mode
+                match self.resolve_neighbor(row_i + dr, col_i + dc) {
+                    // 2. Check if the neighbor is actually alive
+                    Some((neighbor_row, neighbor_col)) => {
+                        self.get_copy(neighbor_row, neighbor_col) == Some(CellState::Alive)
+                    }
+                    // 3. Off the edge with no in-bounds stand-in: constant edge value
+                    None => self.boundary_mode() == BoundaryMode::Alive,
+                }
+            })
+            .count()
+    }
+}
+
+/// The topmost row and leftmost column holding any of the live cells at `indices`
+/// (given in increasing order) on a board `width` cells wide, or None if there are none.
+fn top_left(mut indices: impl Iterator<Item = usize>, width: usize) -> Option<(usize, usize)> {
+    let first = indices.next()?;
+    let min_c = indices.fold(first % width, |min_c, index| min_c.min(index % width));
+    Some((first / width, min_c))
+}
+
+/// A revision number no grid has had before. Counting is shared by every grid, so
+/// replacing one grid with another always changes the revision a renderer sees.
+fn next_revision() -> u64 {
+    static REVISIONS: AtomicU64 = AtomicU64::new(0);
+    REVISIONS.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The pseudo-random value a live cell at `index` contributes to `Grid::state_hash`.
+/// Derived from the index rather than stored in a table, so it costs no memory.
+pub(crate) fn cell_key(index: usize) -> u64 {
+    splitmix64(index as u64)
+}
+
+// The increment of the SplitMix64 sequence (2^64 divided by the golden ratio).
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The SplitMix64 finalizer: a fast, well-distributed 64-bit mix.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Validates grid dimensions, returning the total number of cells.
+/// The overflow check on the multiplication comes first so a huge request
+/// can never wrap around to a small allocation.
+fn check_dimensions(width: usize, height: usize, max_cells: usize) -> Result<usize, GridError> {
+    let cell_count = width
+        .checked_mul(height)
+        .ok_or(GridError::Overflow { width, height })?;
+    if cell_count > max_cells {
+        return Err(GridError::TooLarge {
+            width,
+            height,
+            max_cells,
+        });
+    }
+    Ok(cell_count)
+}
+
+/// Two grids are equal when they have the same dimensions and cells. Settings and
+/// history (the `universe`, the generation count) don't take part.
+impl PartialEq for Grid {
+    fn eq(&self, other: &Self) -> bool {
+        // Differing hashes rule out equality without touching the cells
+        self.width == other.width
+            && self.height == other.height
+            && self.cell_hash == other.cell_hash
+            && self.cells == other.cells
+    }
+}
+
+/// Shows the settings and counters along with one `#`/`.` string per row, so a
+/// failed `assert_eq!` on small grids shows exactly which cells differ.
+impl fmt::Debug for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = (0..self.height)
+            .map(|row| {
+                self.row_states(row)
+                    .map(|state| match state {
+                        CellState::Alive => '#',
+                        CellState::Dead => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        f.debug_struct("Grid")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("universe", &self.universe.to_string())
+            .field("generation", &self.generation)
+            .field("population", &self.population)
+            .field("cells", &rows)
+            .finish()
+    }
+}
+
+/// Allows printing the grid to console/string.
+/// Primarily used for debugging or simple text output, not the main TUI.
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.width == 0 {
+            return Ok(());
+        }
+        (0..self.height)
+            .map(|row| {
+                self.row_states(row)
+                    .map(|state| match state {
+                        CellState::Alive => '#',
+                        CellState::Dead => '.',
+                    })
+                    .map(|ch| ch.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .try_for_each(|line| writeln!(f, "{}", line))?;
+
+        Ok(())
+    }
+}
+
+/// Parses the `Display` output back into a grid, so `grid.to_string().parse::<Grid>()`
+/// reproduces the same cells. `#` is alive and `.` is dead; whitespace between cells
+/// is optional. The width comes from the first line and every line must match it.
+impl FromStr for Grid {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows: Vec<Vec<CellState>> = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let row = line
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .map(|ch| match ch {
+                    '#' => Ok(CellState::Alive),
+                    '.' => Ok(CellState::Dead),
+                    ch => Err(ParseError::UnexpectedChar { line: i + 1, ch }),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(first) = rows.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError::RaggedRow {
+                        line: i + 1,
+                        expected: first.len(),
+                        found: row.len(),
+                    });
+                }
+            }
+            rows.push(row);
+        }
+
+        let width = rows.first().map_or(0, Vec::len);
+        let mut grid = Grid::try_new(width, rows.len())?;
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &state) in row.iter().enumerate() {
+                grid.set(r, c, state);
+            }
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::NamedPattern;
+
+    #[test]
+    fn density_is_the_live_fraction() {
+        let mut grid = Grid::new(4, 5);
+        assert_eq!(grid.density(), 0.0);
+        grid.fill_region(0, 1, 0, 3, CellState::Alive);
+        assert_eq!(grid.density(), 0.4);
+        assert_eq!(Grid::new(0, 0).density(), 0.0);
+    }
+
+    #[test]
+    fn block_entropy_of_uniform_and_mixed_boards() {
+        let mut grid = Grid::new(8, 8);
+        let empty = grid.block_entropy(2);
+        assert_eq!(empty, 0.0);
+        assert!(empty.is_sign_positive());
+
+        // Half the 1x1 blocks alive, half dead: exactly one bit
+        grid.fill_region(0, 3, 0, 7, CellState::Alive);
+        assert_eq!(grid.block_entropy(1), 1.0);
+        // Every 2x2 block is uniform again, but there are two kinds
+        assert_eq!(grid.block_entropy(2), 1.0);
+        // 4x4 blocks too; partial blocks at the edges of a 9x9 board are ignored
+        grid.resize(9, 9).unwrap();
+        assert_eq!(grid.block_entropy(4), 1.0);
+    }
+
+    #[test]
+    fn detect_cycle_finds_oscillator_periods() {
+        let mut grid = Grid::new(20, 20);
+        grid.stamp_pattern(NamedPattern::Blinker, 5, 5);
+        assert_eq!(grid.detect_cycle(10), None);
+        grid.next_generation();
+        assert_eq!(grid.detect_cycle(10), None);
+        grid.next_generation();
+        assert_eq!(grid.detect_cycle(10), Some(2));
+
+        let mut grid = Grid::new(20, 20);
+        grid.stamp_pattern(NamedPattern::Pulsar, 3, 3);
+        for _ in 0..3 {
+            grid.next_generation();
+        }
+        assert_eq!(grid.detect_cycle(10), Some(3));
+        assert_eq!(grid.detect_cycle(2), None);
+    }
+
+    #[test]
+    fn detect_cycle_confirms_hash_matches_against_the_cells() {
+        let mut grid = Grid::new(10, 10);
+        grid.stamp_pattern(NamedPattern::Blinker, 4, 4);
+        grid.next_generation();
+        grid.next_generation();
+        assert_eq!(grid.detect_cycle(2), Some(2));
+
+        // Forge a collision: same hash as the current state, different cells
+        let forged = &mut grid.recent_states[0];
+        forged.cells = CellStore::new(100);
+        assert_eq!(grid.detect_cycle(2), None);
+    }
+
+    #[test]
+    fn edits_forget_past_generations() {
+        let mut grid = Grid::new(10, 10);
+        grid.stamp_pattern(NamedPattern::Blinker, 4, 4);
+        grid.next_generation();
+        grid.next_generation();
+        grid.toggle_cell(0, 0);
+        grid.toggle_cell(0, 0);
+        assert_eq!(grid.detect_cycle(2), None);
+    }
+
+    #[test]
+    fn revision_moves_on_with_every_change() {
+        let mut grid = Grid::new(5, 5);
+        let other = Grid::new(5, 5);
+        assert_ne!(grid.revision(), other.revision());
+
+        let start = grid.revision();
+        grid.set(1, 1, CellState::Dead);
+        assert_eq!(grid.revision(), start);
+        grid.set(1, 1, CellState::Alive);
+        let edited = grid.revision();
+        assert_ne!(edited, start);
+
+        let copy = grid.clone();
+        assert_eq!(copy.revision(), edited);
+        grid.next_generation();
+        assert_ne!(grid.revision(), edited);
+        grid.restore(&copy.snapshot());
+        assert_ne!(grid.revision(), edited);
+    }
+
+    #[test]
+    fn load_pattern_places_a_glider_at_an_offset() {
+        let mut grid = Grid::new(8, 8);
+        grid.load_pattern(".#.\n..#\n###", 2, 3);
+        let mut expected = Grid::new(8, 8);
+        expected.set_alive(&[(2, 4), (3, 5), (4, 3), (4, 4), (4, 5)]);
+        assert_eq!(grid, expected);
+    }
+
+    #[test]
+    fn load_pattern_counts_spaces_and_blank_lines() {
+        let mut grid = Grid::new(4, 4);
+        grid.load_pattern(" #\n #\n #", 0, 0);
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 1), (2, 1)]
+        );
+
+        let mut grid = Grid::new(4, 4);
+        grid.load_pattern("\n  #", 0, 0);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn load_pattern_clips_and_overlays() {
+        let mut grid = Grid::new(4, 4);
+        grid.set_alive(&[(0, 0)]);
+        grid.load_pattern("##\n##", 3, 3);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(0, 0), (3, 3)]);
+
+        grid.load_pattern("#", usize::MAX, usize::MAX);
+        assert_eq!(grid.population(), 2);
+    }
+
+    #[test]
+    fn randomize_is_reproducible_and_matches_density() {
+        let mut first = Grid::new(200, 200);
+        let mut second = Grid::new(200, 200);
+        first.randomize(0.3, 42);
+        second.randomize(0.3, 42);
+        assert_eq!(first, second);
+
+        let density = first.density();
+        assert!((0.28..0.32).contains(&density), "density was {density}");
+
+        first.randomize(0.0, 7);
+        assert_eq!(first.population(), 0);
+        first.randomize(1.0, 7);
+        assert_eq!(first.population(), 200 * 200);
+    }
+
+    #[test]
+    fn randomize_seeds_are_independent() {
+        // Unmixed, a seed one step further along the sequence gave the same board
+        // shifted by a single cell
+        let mut board = Grid::new(64, 64);
+        let mut shifted = Grid::new(64, 64);
+        board.randomize(0.5, 0);
+        shifted.randomize(0.5, GOLDEN_GAMMA);
+        shifted.translate(0, 1);
+        let matching = board
+            .live_cells()
+            .filter(|&(r, c)| shifted.get_copy(r, c) == Some(CellState::Alive))
+            .count();
+        assert!(matching < board.population() * 2 / 3);
+
+        board.randomize(0.5, 1);
+        shifted.randomize(0.5, 2);
+        assert_ne!(board, shifted);
+    }
+
+    #[test]
+    fn translate_drops_cells_pushed_off_the_board() {
+        let mut grid = Grid::new(5, 5);
+        grid.set_alive(&[(0, 0), (2, 3), (4, 4)]);
+        grid.translate(1, -1);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(3, 2)]);
+        assert_eq!(grid.population(), 1);
+    }
+
+    #[test]
+    fn translate_wraps_on_a_torus() {
+        let mut grid = Grid::with_boundary(5, 4, BoundaryMode::Wrap);
+        grid.set_alive(&[(0, 0), (3, 4)]);
+        grid.translate(-1, 2);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(2, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn translate_handles_extreme_offsets() {
+        let mut grid = Grid::new(5, 5);
+        grid.set_alive(&[(2, 2)]);
+        grid.translate(isize::MAX, 0);
+        assert_eq!(grid.population(), 0);
+
+        grid.set_alive(&[(2, 2)]);
+        grid.translate(0, isize::MIN);
+        assert_eq!(grid.population(), 0);
+
+        let mut torus = Grid::with_boundary(5, 5, BoundaryMode::Wrap);
+        torus.set_alive(&[(2, 2)]);
+        torus.translate(isize::MAX, isize::MIN);
+        // isize::MAX is 2 more than a multiple of 5, and isize::MIN 3 less
+        assert_eq!(torus.live_cells().collect::<Vec<_>>(), vec![(4, 4)]);
+    }
+
+    #[test]
+    fn translate_leaves_empty_boards_alone() {
+        for (width, height) in [(0, 0), (5, 0), (0, 5)] {
+            let mut torus = Grid::with_boundary(width, height, BoundaryMode::Wrap);
+            torus.translate(1, -1);
+            assert_eq!((torus.width, torus.height), (width, height));
+        }
+        let mut grid = Grid::from_plaintext("").unwrap();
+        grid.set_boundary_mode(BoundaryMode::Wrap);
+        grid.translate(3, 3);
+        assert_eq!(grid.population(), 0);
+    }
+
+    #[test]
+    fn wrapped_glider_returns_to_its_start() {
+        // A glider moves one cell diagonally every 4 generations, so it needs 40 to
+        // cross a 10x10 torus and come back
+        let mut grid = Grid::with_boundary(10, 10, BoundaryMode::Wrap);
+        grid.stamp_pattern(NamedPattern::Glider, 0, 0);
+        let start = grid.clone();
+        for generation in 1..=40 {
+            grid.next_generation();
+            assert_eq!(grid.population(), 5, "generation {generation}");
+            if generation < 40 {
+                assert_ne!(grid, start, "generation {generation}");
+            }
+        }
+        assert_eq!(grid, start);
+    }
+
+    #[test]
+    fn corner_neighbors_under_each_boundary_mode() {
+        // Live cells on the far row and column, which only a wrapped corner can see
+        let seed = [(0, 1), (1, 1), (4, 0), (0, 4), (4, 4)];
+        let count = |mode| {
+            let mut grid = Grid::with_boundary(5, 5, mode);
+            grid.set_alive(&seed);
+            grid.count_live_neighbors(0, 0)
+        };
+        // Only the in-bounds neighbors (0, 1) and (1, 1)
+        assert_eq!(count(BoundaryMode::Dead), 2);
+        // Plus the far corner and the far ends of row 0 and column 0
+        assert_eq!(count(BoundaryMode::Wrap), 5);
+        // Plus (0, 1) again, reflected to stand in for (-1, 1)
+        assert_eq!(count(BoundaryMode::Mirror), 3);
+    }
+
+    #[test]
+    fn alive_edges_count_every_off_board_neighbor() {
+        // 5 of a corner cell's 8 neighbors are off the board
+        let grid = Grid::with_boundary(4, 4, BoundaryMode::Alive);
+        assert_eq!(grid.count_live_neighbors(0, 0), 5);
+        assert_eq!(grid.count_live_neighbors(0, 2), 3);
+        assert_eq!(grid.count_live_neighbors(2, 2), 0);
+    }
+
+    #[test]
+    fn mirror_edges_reflect_a_lone_corner_cell() {
+        // (0, 0) stands in for (-1, -1), (-1, 0) and (0, -1)
+        let mut grid = Grid::with_boundary(4, 4, BoundaryMode::Mirror);
+        grid.set_alive(&[(0, 0)]);
+        assert_eq!(grid.count_live_neighbors(0, 0), 3);
+        // Seen from (0, 1): (0, 0) itself, and again standing in for (-1, 0)
+        assert_eq!(grid.count_live_neighbors(0, 1), 2);
+        assert_eq!(grid.count_live_neighbors(1, 1), 1);
+    }
+
+    #[test]
+    fn set_alive_seeds_a_glider() {
+        let mut grid = Grid::new(5, 5);
+        // Out-of-bounds coordinates are skipped, like `set` does
+        grid.set_alive(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2), (5, 0), (0, 9)]);
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.get_copy(1, 2), Some(CellState::Alive));
+        assert_eq!(grid.get_copy(1, 1), Some(CellState::Dead));
+    }
+
+    #[test]
+    fn display_output_parses_back_to_the_same_grid() {
+        let mut grid = Grid::new(5, 5);
+        grid.set_alive(&[(0, 0), (1, 3), (2, 1), (2, 2), (4, 4)]);
+        let text = grid.to_string();
+        assert_eq!(text.lines().next(), Some("# . . . ."));
+        assert_eq!(text.parse::<Grid>().unwrap(), grid);
+    }
+
+    #[test]
+    fn parsing_rejects_ragged_rows_and_unknown_characters() {
+        assert_eq!(
+            "# .\n# . .".parse::<Grid>(),
+            Err(ParseError::RaggedRow {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            "# .\n# o".parse::<Grid>(),
+            Err(ParseError::UnexpectedChar { line: 2, ch: 'o' })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip_keeps_cells_and_settings() {
+        let mut grid = Grid::with_boundary(3, 2, BoundaryMode::Wrap);
+        grid.set_neighborhood(Neighborhood::VonNeumann);
+        grid.set_alive(&[(0, 0), (1, 2)]);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert!(json.contains(r#""cells":["Alive","Dead","Dead","Dead","Dead","Alive"]"#));
+
+        let parsed: Grid = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, grid);
+        assert_eq!(parsed.boundary_mode(), BoundaryMode::Wrap);
+        assert_eq!(parsed.neighborhood(), Neighborhood::VonNeumann);
+        assert_eq!(parsed.population(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_with_the_wrong_cell_count_is_rejected() {
+        let json = r#"{"width":2,"height":2,"cells":["Alive","Dead","Dead"]}"#;
+        let err = serde_json::from_str::<Grid>(json).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected 4 cells for a 2x2 grid, found 3"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_generations_match_the_serial_rule() {
+        let mut grid = Grid::new(300, 200);
+        grid.randomize(0.35, 42);
+        for generation in 1..=10 {
+            // `preview_next` applies the rule serially, one cell after another
+            let expected = grid.preview_next();
+            grid.next_generation();
+            let cells: Vec<CellState> = (0..grid.width * grid.height)
+                .map(|index| grid.cells.get(index))
+                .collect();
+            assert!(cells == expected, "generation {generation}");
+        }
+    }
+
+    /// The population and hash tallied while a generation is computed (per thread,
+    /// with `rayon`) must add up to what a fresh scan of the new cells finds.
+    #[test]
+    fn tallied_population_and_hash_match_a_fresh_scan() {
+        for boundary in [BoundaryMode::Dead, BoundaryMode::Wrap] {
+            let mut grid = Grid::with_boundary(300, 200, boundary);
+            grid.randomize(0.35, 9);
+            for generation in 1..=20 {
+                grid.next_generation();
+                let (hash, population) = grid
+                    .cells
+                    .alive_indices()
+                    .fold((0, 0), |(hash, population), index| {
+                        (hash ^ cell_key(index), population + 1)
+                    });
+                let context = format!("{boundary:?}, gen {generation}");
+                assert_eq!(grid.population(), population, "{context}");
+                assert_eq!(grid.cell_hash, hash, "{context}");
+            }
+        }
+    }
+
+    #[test]
+    fn ppm_export_writes_the_header_and_pixels() {
+        let mut grid = Grid::new(2, 2);
+        grid.set_alive(&[(0, 1), (1, 0)]);
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0]);
+        assert_eq!(grid.to_ppm(1), expected);
+
+        // Each cell becomes a 2x2 block
+        let ppm = grid.to_ppm(2);
+        let pixels = &ppm[b"P6\n4 4\n255\n".len()..];
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        assert_eq!(
+            &pixels[..12],
+            &[0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255]
+        );
+        assert_eq!(&pixels[..12], &pixels[12..24]);
+    }
+
+    #[test]
+    fn population_follows_every_kind_of_change() {
+        let mut grid = Grid::new(6, 6);
+        assert_eq!(grid.population(), 0);
+        grid.stamp_pattern(NamedPattern::Blinker, 2, 1);
+        assert_eq!(grid.population(), 3);
+        grid.toggle_cell(0, 0);
+        grid.set(0, 0, CellState::Alive); // Already alive: no change
+        assert_eq!(grid.population(), 4);
+        grid.set(0, 0, CellState::Dead);
+        assert_eq!(grid.population(), 3);
+        grid.next_generation();
+        assert_eq!(grid.population(), 3);
+        grid.fill_region(0, 5, 0, 5, CellState::Dead);
+        assert_eq!(grid.population(), 0);
+    }
+
+    #[test]
+    fn generation_counts_steps_until_a_reset() {
+        let mut grid = Grid::new(5, 5);
+        assert_eq!(grid.generation(), 0);
+        for _ in 0..10 {
+            grid.next_generation();
+        }
+        assert_eq!(grid.generation(), 10);
+        // Editing doesn't restart the count
+        grid.toggle_cell(2, 2);
+        assert_eq!(grid.generation(), 10);
+        grid.reset();
+        assert_eq!(grid.generation(), 0);
+    }
+
+    #[test]
+    fn stability_is_detected_for_still_lifes_and_empty_boards() {
+        let mut grid = Grid::new(6, 6);
+        grid.set_alive(&[(1, 1), (1, 2), (2, 1), (2, 2)]);
+        assert!(!grid.is_stable());
+        grid.next_generation();
+        assert!(grid.is_stable());
+        // An edit means the next generation might change something
+        grid.toggle_cell(5, 5);
+        assert!(!grid.is_stable());
+
+        // A blinker changes every generation
+        let mut grid = Grid::new(6, 6);
+        grid.stamp_pattern(NamedPattern::Blinker, 2, 1);
+        grid.next_generation();
+        assert!(!grid.is_stable());
+
+        // A lone cell dies, then nothing changes
+        let mut grid = Grid::new(6, 6);
+        grid.toggle_cell(3, 3);
+        grid.next_generation();
+        assert!(!grid.is_stable());
+        grid.next_generation();
+        assert!(grid.is_stable());
+        assert_eq!(grid.population(), 0);
+    }
+
+    #[test]
+    fn live_cells_yields_the_seeded_cells_in_row_major_order() {
+        let mut grid = Grid::new(6, 4);
+        assert_eq!(grid.live_cells().next(), None);
+        grid.set_alive(&[(3, 5), (0, 4), (2, 0), (0, 1)]);
+        let live: Vec<(usize, usize)> = grid.live_cells().collect();
+        assert_eq!(live, vec![(0, 1), (0, 4), (2, 0), (3, 5)]);
+    }
+
+    #[test]
+    fn live_bounds_spans_opposite_corners() {
+        let mut grid = Grid::new(7, 5);
+        assert_eq!(grid.live_bounds(), None);
+        grid.set_alive(&[(0, 6), (4, 0)]);
+        assert_eq!(grid.live_bounds(), Some((0, 4, 0, 6)));
+
+        let mut grid = Grid::new(7, 5);
+        grid.stamp_pattern(NamedPattern::Glider, 1, 2);
+        assert_eq!(grid.live_bounds(), Some((1, 3, 2, 4)));
+    }
+
+    #[test]
+    fn resize_keeps_the_cells_that_still_fit() {
+        let mut grid = Grid::new(4, 4);
+        grid.next_generation();
+        grid.set_alive(&[(0, 0), (1, 3), (3, 1)]);
+
+        grid.resize(6, 5).unwrap();
+        assert_eq!((grid.width, grid.height), (6, 5));
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 3), (3, 1)]
+        );
+        assert_eq!(grid.generation(), 1);
+
+        grid.resize(3, 2).unwrap();
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(0, 0)]);
+        assert_eq!(grid.population(), 1);
+
+        // Rejected sizes leave the grid as it was
+        assert!(grid.resize(usize::MAX, 2).is_err());
+        assert_eq!((grid.width, grid.height), (3, 2));
+    }
+
+    #[test]
+    fn a_clone_is_equal_until_it_moves_on() {
+        let mut grid = Grid::new(6, 6);
+        grid.stamp_pattern(NamedPattern::Blinker, 2, 1);
+        let mut clone = grid.clone();
+        assert_eq!(clone, grid);
+        clone.next_generation();
+        assert_ne!(clone, grid);
+        // The original is untouched
+        assert_eq!(grid.get_copy(2, 1), Some(CellState::Alive));
+        clone.next_generation();
+        assert_eq!(clone, grid);
+    }
+
+    #[test]
+    fn von_neumann_counts_only_orthogonal_neighbors() {
+        let mut grid = Grid::new(5, 5);
+        // A plus sign centered on (2, 2)
+        grid.set_alive(&[(1, 2), (2, 1), (2, 2), (2, 3), (3, 2)]);
+        assert_eq!(grid.count_live_neighbors(2, 2), 4);
+        assert_eq!(grid.count_live_neighbors(1, 1), 3);
+        assert_eq!(grid.count_live_neighbors(0, 2), 1);
+
+        grid.set_neighborhood(Neighborhood::VonNeumann);
+        assert_eq!(grid.count_live_neighbors(2, 2), 4);
+        // The diagonal (2, 2) no longer counts
+        assert_eq!(grid.count_live_neighbors(1, 1), 2);
+        assert_eq!(grid.count_live_neighbors(0, 2), 1);
+        assert_eq!(grid.count_live_neighbors(0, 1), 0);
+    }
+
+    #[test]
+    fn fill_region_sets_every_cell_and_clips_at_the_edges() {
+        let mut grid = Grid::new(5, 4);
+        grid.fill_region(1, 2, 1, 3, CellState::Alive);
+        assert_eq!(grid.population(), 6);
+        assert_eq!(grid.live_bounds(), Some((1, 2, 1, 3)));
+
+        // Filling over live cells leaves them alive; the region past the edge is clipped
+        grid.fill_region(2, 10, 3, 10, CellState::Alive);
+        assert_eq!(grid.population(), 6 + 3);
+        assert_eq!(grid.live_bounds(), Some((1, 3, 1, 4)));
+
+        grid.fill_region(0, 99, 0, 1, CellState::Dead);
+        assert_eq!(grid.population(), 9 - 2);
+        // Entirely outside: nothing happens
+        grid.fill_region(7, 9, 0, 4, CellState::Alive);
+        assert_eq!(grid.population(), 7);
+    }
+
+    #[test]
+    fn ages_count_generations_alive_when_tracked() {
+        let mut grid = Grid::new(6, 6);
+        grid.stamp_pattern(NamedPattern::Blinker, 2, 1);
+        grid.next_generation();
+        // Off by default
+        assert_eq!(grid.age(2, 2), 0);
+
+        grid.set_age_tracking(true);
+        grid.next_generation();
+        grid.next_generation();
+        // The blinker's middle cell survives every generation; its ends are reborn
+        assert_eq!(grid.age(2, 2), 2);
+        assert_eq!(grid.age(2, 1), 0);
+        assert_eq!(grid.age(2, 3), 0);
+        assert_eq!(grid.age(0, 0), 0);
+        assert_eq!(grid.age(9, 9), 0);
+
+        // Edits that replace the board start everyone over
+        grid.translate(1, 0);
+        assert_eq!(grid.age(3, 2), 0);
+        grid.set_age_tracking(false);
+        assert!(!grid.age_tracking());
+    }
+
+    #[test]
+    fn four_quarter_turns_restore_a_glider() {
+        let mut grid = Grid::new(7, 4);
+        grid.stamp_pattern(NamedPattern::Glider, 0, 1);
+        let start = grid.clone();
+
+        grid.rotate_cw();
+        assert_eq!((grid.width, grid.height), (4, 7));
+        assert_ne!(grid, start);
+        for _ in 0..3 {
+            grid.rotate_cw();
+        }
+        assert_eq!(grid, start);
+
+        grid.rotate_ccw();
+        grid.rotate_cw();
+        assert_eq!(grid, start);
+        grid.flip_horizontal();
+        grid.flip_horizontal();
+        grid.flip_vertical();
+        grid.flip_vertical();
+        assert_eq!(grid, start);
+    }
+
+    #[test]
+    fn transform_region_turns_only_the_selection() {
+        let mut grid = Grid::new(8, 8);
+        grid.set_alive(&[(0, 0), (0, 1), (7, 7)]);
+        grid.transform_region(Transform::RotateCw, 0, 1, 0, 1);
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            vec![(0, 1), (1, 1), (7, 7)]
+        );
+        grid.transform_region(Transform::FlipHorizontal, 0, 1, 0, 1);
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (7, 7)]
+        );
+    }
+
+    #[test]
+    fn edge_and_corner_cells_have_fewer_neighbors() {
+        // On a full board every neighbor that exists is alive
+        let mut grid = Grid::new(4, 3);
+        grid.fill_region(0, 2, 0, 3, CellState::Alive);
+        assert_eq!(grid.count_live_neighbors(1, 1), 8);
+        assert_eq!(grid.count_live_neighbors(0, 1), 5);
+        assert_eq!(grid.count_live_neighbors(1, 3), 5);
+        assert_eq!(grid.count_live_neighbors(0, 0), 3);
+        assert_eq!(grid.count_live_neighbors(2, 3), 3);
+    }
+
+    #[test]
+    fn restore_rolls_back_cells_dimensions_and_generation() {
+        let mut grid = Grid::new(6, 6);
+        grid.stamp_pattern(NamedPattern::Glider, 0, 0);
+        let snapshot = grid.snapshot();
+        let start = grid.clone();
+
+        for _ in 0..3 {
+            grid.next_generation();
+        }
+        grid.resize(9, 4).unwrap();
+        grid.restore(&snapshot);
+        assert_eq!(grid, start);
+        assert_eq!((snapshot.width(), snapshot.height()), (6, 6));
+        assert_eq!(grid.generation(), 0);
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.state_hash(), start.state_hash());
+
+        // It simulates on from there exactly as the original would
+        let mut original = start.clone();
+        original.next_generation();
+        grid.next_generation();
+        assert_eq!(grid, original);
+    }
+
+    #[test]
+    fn detect_spaceship_finds_the_period_and_move() {
+        let mut grid = Grid::new(20, 20);
+        grid.stamp_pattern(NamedPattern::Glider, 2, 2);
+        for _ in 0..3 {
+            grid.next_generation();
+            assert_eq!(grid.detect_spaceship(10), None);
+        }
+        grid.next_generation();
+        let glider = grid.detect_spaceship(10).unwrap();
+        assert_eq!(
+            glider,
+            Spaceship {
+                period: 4,
+                displacement: (1, 1)
+            }
+        );
+        assert_eq!(glider.to_string(), "glider: period 4, moves (1,1)");
+        assert_eq!(grid.detect_cycle(10), None);
+
+        // The lightweight spaceship moves two cells left every 4 generations
+        let mut grid = Grid::new(20, 20);
+        grid.stamp_pattern(NamedPattern::Lwss, 8, 12);
+        for _ in 0..4 {
+            grid.next_generation();
+        }
+        let lwss = grid.detect_spaceship(10).unwrap();
+        assert_eq!(lwss.displacement, (0, -2));
+        assert_eq!(lwss.to_string(), "spaceship: period 4, moves (0,-2)");
+    }
+
+    #[test]
+    fn oscillators_and_edited_boards_are_not_spaceships() {
+        let mut grid = Grid::new(10, 10);
+        grid.stamp_pattern(NamedPattern::Blinker, 4, 4);
+        for _ in 0..4 {
+            grid.next_generation();
+        }
+        assert_eq!(grid.detect_cycle(10), Some(2));
+        assert_eq!(grid.detect_spaceship(10), None);
+
+        let mut grid = Grid::new(20, 20);
+        grid.stamp_pattern(NamedPattern::Glider, 2, 2);
+        for _ in 0..4 {
+            grid.next_generation();
+        }
+        grid.toggle_cell(19, 19);
+        grid.toggle_cell(19, 19);
+        assert_eq!(grid.detect_spaceship(10), None);
+    }
+
+    #[test]
+    fn with_rule_sets_up_a_non_conway_grid() {
+        use crate::rule::LifelikeRule;
+
+        let highlife: LifelikeRule = "B36/S23".parse().unwrap();
+        let mut grid = Grid::with_rule(8, 6, highlife);
+        assert_eq!((grid.width, grid.height), (8, 6));
+        assert_eq!(LifelikeRule::from_rule(grid.rule()), highlife);
+
+        // A dead cell with six live neighbors is born under HighLife only
+        grid.set_alive(&[(1, 1), (1, 2), (1, 3), (3, 1), (3, 2), (3, 3)]);
+        grid.next_generation();
+        assert_eq!(grid.get_copy(2, 2), Some(CellState::Alive));
+        assert_eq!(
+            LifelikeRule::from_rule(Grid::new(4, 4).rule()).to_string(),
+            "B3/S23"
+        );
+    }
+
+    #[test]
+    fn universe_bundles_the_rule_boundary_and_neighborhood() {
+        use crate::rule::RulePreset;
+
+        assert_eq!(Universe::default().to_string(), "B3/S23, bounded, Moore");
+        assert_eq!(
+            Grid::new(3, 3).universe().to_string(),
+            "B3/S23, bounded, Moore"
+        );
+
+        let universe = Universe {
+            rule: Arc::new(RulePreset::HighLife.rule()),
+            boundary_mode: BoundaryMode::Wrap,
+            neighborhood: Neighborhood::VonNeumann,
+        };
+        let mut grid = Grid::with_universe(6, 4, universe);
+        assert_eq!(grid.universe().to_string(), "B36/S23, torus, von Neumann");
+        assert_eq!(grid.boundary_mode(), BoundaryMode::Wrap);
+        assert_eq!(grid.neighborhood(), Neighborhood::VonNeumann);
+
+        // Changing any part forgets the generations that led here
+        grid.set_neighborhood(Neighborhood::Moore);
+        grid.set_alive(&[(1, 1), (1, 2), (1, 3)]);
+        grid.next_generation();
+        grid.next_generation();
+        assert_eq!(grid.detect_cycle(4), Some(2));
+        grid.set_boundary_mode(BoundaryMode::Dead);
+        assert_eq!(grid.detect_cycle(4), None);
+        assert_eq!(grid.population(), 3);
+        grid.set_universe(Universe::default());
+        assert_eq!(grid.universe().to_string(), "B3/S23, bounded, Moore");
+    }
+
+    #[test]
+    fn step_n_matches_single_steps_and_skips_once_stable() {
+        let mut stepped = Grid::new(16, 16);
+        stepped.load_pattern("###\n.#.", 7, 6);
+        let mut single = stepped.clone();
+        stepped.step_n(12);
+        for _ in 0..12 {
+            single.next_generation();
+        }
+        assert_eq!(stepped, single);
+        assert_eq!(stepped.generation(), 12);
+
+        // A block never changes, so a billion generations finish at once
+        let mut block = Grid::new(4, 4);
+        block.set_alive(&[(1, 1), (1, 2), (2, 1), (2, 2)]);
+        block.step_n(1_000_000_000);
+        assert_eq!(block.generation(), 1_000_000_000);
+        assert_eq!(block.population(), 4);
+    }
+
+    #[test]
+    fn classify_names_what_the_board_settled_into() {
+        let run = |pattern: &str, generations: usize| {
+            let mut grid = Grid::new(20, 20);
+            grid.load_pattern(pattern, 8, 8);
+            for _ in 0..generations {
+                grid.next_generation();
+            }
+            grid.classify()
+        };
+
+        assert_eq!(Grid::new(5, 5).classify(), Classification::Extinct);
+        assert_eq!(run("##", 1), Classification::Extinct);
+        assert_eq!(run("##\n##", 1), Classification::StillLife);
+        assert_eq!(run("###", 2), Classification::Oscillator { period: 2 });
+        assert_eq!(
+            run(".#.\n..#\n###", 4),
+            Classification::Spaceship(Spaceship {
+                period: 4,
+                displacement: (1, 1)
+            })
+        );
+        // The R-pentomino takes over a thousand generations to settle
+        assert_eq!(run(".##\n##.\n.#.", 10), Classification::Chaotic);
+        // Nothing is known about a board that was just drawn
+        assert_eq!(run("###", 0), Classification::Chaotic);
+    }
+
+    #[test]
+    fn classifications_read_as_one_line_summaries() {
+        let glider = Spaceship {
+            period: 4,
+            displacement: (1, -1),
+        };
+        let lwss = Spaceship {
+            period: 4,
+            displacement: (0, -2),
+        };
+        let summaries = [
+            (Classification::Extinct, "extinct"),
+            (Classification::StillLife, "still life"),
+            (
+                Classification::Oscillator { period: 3 },
+                "period-3 oscillator",
+            ),
+            (
+                Classification::Spaceship(glider),
+                "glider: period 4, moves (1,-1)",
+            ),
+            (
+                Classification::Spaceship(lwss),
+                "spaceship: period 4, moves (0,-2)",
+            ),
+            (Classification::Chaotic, "chaotic"),
+        ];
+        for (classification, summary) in summaries {
+            assert_eq!(classification.to_string(), summary);
+        }
+    }
+
+    #[test]
+    fn blocks_stamp_dead_cells_too_and_clip_at_the_edges() {
+        let mut grid = Grid::new(5, 5);
+        grid.set_alive(&[(0, 0), (1, 1)]);
+        let block = grid.copy_block(0, 1, 0, 1);
+        assert_eq!((block.width(), block.height()), (2, 2));
+        assert_eq!(block.get(1, 1), Some(CellState::Alive));
+        assert_eq!(block.get(0, 2), None);
+
+        // Stamped over live cells, the copied dead ones clear them
+        grid.fill_region(3, 4, 3, 4, CellState::Alive);
+        grid.stamp_block(&block, 3, 3);
+        assert_eq!(grid.get_copy(3, 4), Some(CellState::Dead));
+        assert_eq!(grid.get_copy(4, 3), Some(CellState::Dead));
+        assert_eq!(grid.population(), 4);
+
+        // Only the top-left cell fits in the corner
+        let mut grid = Grid::new(5, 5);
+        grid.stamp_block(&block, 4, 4);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(4, 4)]);
+    }
+
+    #[test]
+    fn tiling_toggles_under_live_cells_and_cuts_off_partial_copies() {
+        let mut source = Grid::new(3, 2);
+        source.load_pattern("#..\n.#.", 0, 0);
+        let block = source.copy_block(0, 1, 0, 2);
+
+        // 8 columns hold two whole copies and two columns of a third
+        let mut grid = Grid::new(10, 5);
+        grid.tile_region(0, 2, 0, 7, &block);
+        let tiled = grid.live_cells().collect::<Vec<_>>();
+        assert_eq!(
+            tiled,
+            vec![
+                (0, 0),
+                (0, 3),
+                (0, 6),
+                (1, 1),
+                (1, 4),
+                (1, 7),
+                (2, 0),
+                (2, 3),
+                (2, 6)
+            ]
+        );
+
+        // Tiled again it toggles the same cells back off, while the cell under a dead
+        // one of the block is left as it was
+        grid.set(0, 1, CellState::Alive);
+        grid.tile_region(0, 2, 0, 7, &block);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(0, 1)]);
+
+        // The part of the region past the grid is clipped
+        let mut grid = Grid::new(4, 4);
+        grid.tile_region(2, 9, 2, 9, &block);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn growing_adds_dead_cells_around_the_board() {
+        let mut grid = Grid::new(4, 3);
+        grid.load_pattern("###", 1, 1);
+        grid.next_generation();
+        let before = grid.clone();
+
+        grid.grow(2, 1, 3, 0).unwrap();
+        assert_eq!((grid.width, grid.height), (7, 6));
+        assert_eq!(grid.generation(), 1);
+        let moved: Vec<_> = before.live_cells().map(|(r, c)| (r + 2, c + 3)).collect();
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), moved);
+        assert_eq!(grid.population(), 3);
+
+        assert!(grid.grow(0, 0, usize::MAX, 0).is_err());
+        assert_eq!((grid.width, grid.height), (7, 6));
+    }
+
+    #[test]
+    fn sparse_backend_steps_like_dense() {
+        let rules = ["B3/S23", "B36/S23", "B0/S8", "B2/S"];
+        for mode in [
+            BoundaryMode::Dead,
+            BoundaryMode::Alive,
+            BoundaryMode::Mirror,
+            BoundaryMode::Wrap,
+        ] {
+            for neighborhood in [Neighborhood::Moore, Neighborhood::VonNeumann] {
+                for rule in rules {
+                    let mut dense = Grid::new(12, 15);
+                    dense.set_boundary_mode(mode);
+                    dense.set_neighborhood(neighborhood);
+                    dense.set_rule(rule.parse::<LifelikeRule>().unwrap());
+                    dense.randomize(0.2, 7);
+                    let mut sparse = dense.clone();
+                    sparse.set_backend(Backend::Sparse);
+                    for _ in 0..6 {
+                        dense.next_generation();
+                        sparse.next_generation();
+                        assert!(
+                            sparse.snapshot() == dense.snapshot(),
+                            "{rule} {mode:?} {neighborhood:?}"
+                        );
+                        assert_eq!(sparse.population(), dense.population());
+                        assert_eq!(sparse.state_hash(), dense.state_hash());
+                    }
+                    assert_eq!(sparse.backend(), Backend::Sparse);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_backend_steps_an_empty_board() {
+        for (width, height) in [(5, 0), (0, 5), (0, 0)] {
+            let mut grid = Grid::with_boundary(width, height, BoundaryMode::Alive);
+            grid.set_backend(Backend::Sparse);
+            grid.next_generation();
+            assert_eq!(grid.population(), 0);
+            assert_eq!(grid.generation(), 1);
+        }
+    }
+
+    #[test]
+    fn switching_backends_keeps_the_cells() {
+        let mut grid = Grid::new(10, 10);
+        grid.stamp_pattern(NamedPattern::Glider, 2, 2);
+        let dense = grid.snapshot();
+        grid.set_backend(Backend::Sparse);
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.get(2, 3), Some(&CellState::Alive));
+        grid.resize(12, 12).unwrap();
+        assert_eq!(grid.backend(), Backend::Sparse);
+        grid.restore(&dense);
+        assert_eq!(grid.backend(), Backend::Sparse);
+        assert_eq!(grid.population(), 5);
+        grid.set_backend(Backend::Dense);
+        assert!(grid.snapshot() == dense);
     }
 }
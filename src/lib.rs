@@ -1 +1,7 @@
-pub mod grid;
\ No newline at end of file
+pub mod grid;
+pub mod patterns;
+pub mod plaintext;
+pub mod rle;
+pub mod rule;
+pub mod sparse;
+mod storage;
@@ -1,44 +1,899 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt::Display;
-use std::io;
-use std::time::{Duration, Instant};
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-// We use crossterm for handling raw input events (keyboard presses)
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+// We use crossterm for handling raw input events (keyboard presses, mouse clicks)
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
 // Ratatui handles the actual drawing of widgets to the terminal
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Constraint, Flex, Layout, Rect},
     style::{Color, Style, Stylize},
     symbols::border,
     text::{Line, Span, Text},
-    widgets::{Block, Paragraph, Widget},
+    widgets::{Block, Clear, Padding, Paragraph, Widget},
     DefaultTerminal, Frame,
 };
 
-use conway_game_of_rust::grid::{CellState, Grid};
+use conway_game_of_rust::grid::{
+    Backend, Block as CellBlock, BoundaryMode, CellState, Classification, Grid, GridSnapshot,
+    Transform, CYCLE_HISTORY_LEN,
+};
+use conway_game_of_rust::patterns::NamedPattern;
+use conway_game_of_rust::rule::{LifelikeRule, RulePreset};
+
+// File in the slot directory that the board is autosaved to on exit, for `--restore`.
+// Not `.rle`, so it stays out of the `:load` menu.
+const SESSION_FILE: &str = "session.txt";
 
-// Sets the speed of the simulation, will be mutable in the future.
+// Default speed of the simulation (ms per generation), adjustable at runtime.
 const TIME_BETWEEN_GENERATIONS: u64 = 150;
 
+// Speed presets selected with Alt+1 (slowest) through Alt+9 (fastest), in ms per generation.
+// Preset 5 matches the default speed, and preset 9 the fastest `+` allows (MIN_TICK_MS).
+const SPEED_PRESETS: [u64; 9] = [1000, 600, 400, 250, 150, 100, 60, 30, 20];
+
+// Range the `+` / `-` keys can move the interval within, in ms per generation.
+const MIN_TICK_MS: u64 = 20;
+const MAX_TICK_MS: u64 = 2000;
+
+// Most generations run in a single frame to catch up after a slow frame. Beyond this the
+// backlog is dropped so a long stall can't snowball into an ever-growing catch-up.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+
+// How long a `:run` computes generations before handing back to draw its progress and
+// check for Esc.
+const RUN_CHUNK_BUDGET: Duration = Duration::from_millis(50);
+
+// How close to an edge a live cell can get before `:set expand on` grows the board,
+// so the next generation is never cut off there.
+const EXPAND_MARGIN: usize = 2;
+
+// Dead rows or columns added at an edge each time `:set expand on` grows the board.
+const EXPAND_STEP: usize = 32;
+
+// The widest and tallest `:set expand on` grows the board, so a pattern that keeps
+// spreading can't use up all the memory.
+const MAX_EXPANDED_SIDE: usize = 4096;
+
+// With `:backend auto`, a board moves to the sparse backend once its density falls
+// below `SPARSE_BELOW`, and back to the dense one once it climbs above `DENSE_ABOVE`.
+// The gap between them keeps a board hovering near one threshold from switching back
+// and forth every generation.
+const SPARSE_BELOW: f64 = 0.01;
+const DENSE_ABOVE: f64 = 0.03;
+
+// Fraction of cells brought to life by the randomize key.
+const RANDOM_DENSITY: f64 = 0.3;
+
+// Side of the square blocks whose patterns the status line's entropy readout counts.
+// 2x2 blocks have 16 possible patterns, so the readout ranges from 0 to 4 bits.
+const ENTROPY_BLOCK_SIZE: usize = 2;
+
+// Default for how many edits are kept for undo, and separately how many past
+// generations for stepping back. Each one is a snapshot of every cell, so this bounds
+// memory; `--history` and `:set history` change it.
+const HISTORY_LIMIT: usize = 100;
+
+// Starting radius for the disk stamp tool, adjustable at runtime.
+const DEFAULT_DISK_RADIUS: usize = 3;
+
+// How long a partially typed key sequence (e.g. the first `g` of `gg`) waits for
+// its next key before being discarded.
+const KEY_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Actions bound to multi-key sequences (see `KEY_SEQUENCES`).
+#[derive(Clone, Copy)]
+enum SequenceAction {
+    JumpToTop,
+    CenterView,
+    ClearRow,
+    ClearCol,
+}
+
+// Multi-key sequences and the modes they're available in. Keys are buffered in
+// `App::pending_keys` until they form one of these (or can no longer become one).
+// In VISUAL mode `d` acts on the selection straight away, so `dd` / `dc` are NORMAL only.
+const KEY_SEQUENCES: &[(&str, SequenceAction, &[Mode])] = &[
+    ("gg", SequenceAction::JumpToTop, &[N, V]),
+    ("zz", SequenceAction::CenterView, &[N, V]),
+    ("dd", SequenceAction::ClearRow, &[N]),
+    ("dc", SequenceAction::ClearCol, &[N]),
+];
+
+// Smallest terminal (in characters) we attempt to draw the grid in. Below this the
+// border, title and instructions leave no usable room, so a notice is shown instead.
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 8;
+
+/// A key binding as shown to the user: what it does, the key(s) to press, and the
+/// modes it applies in. `{radius}`, `{tick_ms}` and `{pattern}` in an action are replaced
+/// with the current disk radius, generation interval and selected library pattern.
+struct Binding {
+    action: &'static str,
+    keys: &'static str,
+    modes: &'static [Mode],
+}
+
+const fn bind(action: &'static str, keys: &'static str, modes: &'static [Mode]) -> Binding {
+    Binding {
+        action,
+        keys,
+        modes,
+    }
+}
+
+const N: Mode = Mode::NORMAL;
+const R: Mode = Mode::RUNNING;
+const V: Mode = Mode::VISUAL;
+
+// Every binding handled in `App::handle_key_event`, in the order they're listed in the
+// instruction bar. Add new bindings here so the on-screen help stays in sync.
+const BINDINGS: &[Binding] = &[
+    bind("Reset", "<R>", &[N, V]),
+    bind("Randomize", "<Shift-R>", &[N, V]),
+    bind("Selection Movement", "hjkl / ← ↓ ↑ →", &[N, V]),
+    bind("Jump to Edge", "<gg> / <G> / <0> / <$>", &[N, V]),
+    bind("Center View", "<zz>", &[N, V]),
+    bind("Pause/Unpause Simulation", "<Enter>", &[N, R, V]),
+    bind("Step / Step Back", "<N> / <B>", &[N]),
+    bind("Speed ({tick_ms}ms/gen)", "<+> / <->", &[N, R]),
+    bind("Speed Preset", "<Alt-1..9>", &[N, R]),
+    bind("Toggle Selected Cell(s)", "<Space>", &[N, V]),
+    bind("Toggle Cell", "<Click>", &[N, V]),
+    bind("Paint / Erase", "<Drag> / <Right-Drag>", &[N, V]),
+    bind("Stamp Disk", "<C>", &[N]),
+    bind("Disk Radius ({radius}) Smaller / Larger", "<<> / <>>", &[N]),
+    bind("Place {pattern} / Next Pattern", "<I> / <Tab>", &[N]),
+    bind("Outline Selection", "<O>", &[V]),
+    bind("Pin Region", "<Shift-P>", &[V]),
+    bind("Yank", "<Y>", &[V]),
+    bind("Paste", "<P>", &[N]),
+    bind("Tile with Yanked", "<T>", &[V]),
+    bind("Copy Board as RLE", "<Shift-Y>", &[N, R]),
+    bind("Draw Line", "<L>", &[V]),
+    bind("Fill Alive / Dead", "<A> / <D>", &[V]),
+    bind("Rotate Right / Left", "<>> / <<>", &[V]),
+    bind("Flip Horizontal / Vertical", "<|> / <_>", &[V]),
+    bind("Clear Row / Column", "<dd> / <dc>", &[N]),
+    bind("Mirror", "<S>", &[N]),
+    bind("Heatmap", "<Shift-H>", &[N, R]),
+    bind("Age Colors", "<Shift-E>", &[N, R]),
+    bind("Preview Changes", "<Shift-F>", &[N, V]),
+    bind("Aspect", "<Shift-A>", &[N, R]),
+    bind("Theme", "<Shift-T>", &[N, R]),
+    bind("Wrap Edges", "<Shift-W>", &[N, R, V]),
+    bind("Undo / Redo", "<U> / <Ctrl-R>", &[N, V]),
+    bind("Visual Mode", "<V>", &[N]),
+    bind("Command Mode", "<:>", &[N, V]),
+    bind("Save / Load", "<W> / <E>", &[N]),
+    bind("Normal Mode", "<Esc>", &[V]),
+    bind("Help", "<?>", &[N, R, V]),
+    bind("Quit", "<Q>", &[N, R, V]),
+];
+
+// Modes listed in the help overlay, each with the bindings that apply in it.
+const HELP_MODES: [Mode; 3] = [Mode::NORMAL, Mode::VISUAL, Mode::RUNNING];
+
+/// The colors used to draw the grid. Every render style pulls its colors from here.
+struct Theme {
+    name: &'static str,
+    alive: Color,               // Live cell glyphs
+    dead: Color,                // Dead cell `░░` texture
+    empty: Color,               // Dead cells in half-block mode
+    cursor_fg: Color,           // Glyph drawn under the cursor
+    cursor_bg: Color,           // Cursor highlight
+    selection_fg: Color,        // Glyph drawn inside the visual selection
+    selection_bg: Color,        // Visual selection highlight
+    half_cursor_alive: Color,   // Half-block cursor over a live cell
+    half_cursor_dead: Color,    // Half-block cursor over a dead cell
+    half_selection_dead: Color, // Half-block dead cell inside the selection
+    region: Color,              // Background of the pinned analysis region
+    birth: Color,               // Dead cell that will be born next generation
+    death: Color,               // Live cell that will die next generation
+}
+
+// The original look of the app.
+const CLASSIC_THEME: Theme = Theme {
+    name: "Classic",
+    alive: Color::White,
+    dead: Color::White,
+    empty: Color::DarkGray,
+    cursor_fg: Color::DarkGray,
+    cursor_bg: Color::White,
+    selection_fg: Color::White,
+    selection_bg: Color::LightBlue,
+    half_cursor_alive: Color::LightYellow,
+    half_cursor_dead: Color::Yellow,
+    half_selection_dead: Color::Blue,
+    region: Color::Rgb(0x30, 0x30, 0x50),
+    birth: Color::Green,
+    death: Color::Red,
+};
+
+// Colorblind-safe palette built from the Okabe-Ito colors, relying on luminance
+// differences between roles rather than red/green or blue/white hue alone.
+const COLORBLIND_THEME: Theme = Theme {
+    name: "Colorblind",
+    alive: Color::Rgb(0xF0, 0xE4, 0x42), // Yellow
+    dead: Color::Rgb(0x80, 0x80, 0x80),  // Mid gray
+    empty: Color::Rgb(0x26, 0x26, 0x26), // Near black
+    cursor_fg: Color::Black,
+    cursor_bg: Color::Rgb(0xD5, 0x5E, 0x00), // Vermillion
+    selection_fg: Color::White,
+    selection_bg: Color::Rgb(0x56, 0xB4, 0xE9), // Sky blue
+    half_cursor_alive: Color::Rgb(0xE6, 0x9F, 0x00), // Orange
+    half_cursor_dead: Color::Rgb(0xD5, 0x5E, 0x00), // Vermillion
+    half_selection_dead: Color::Rgb(0x00, 0x72, 0xB2), // Blue
+    region: Color::Rgb(0x00, 0x4F, 0x3A),       // Dark bluish green
+    birth: Color::Rgb(0x00, 0x9E, 0x73),        // Bluish green
+    death: Color::Rgb(0xCC, 0x79, 0xA7),        // Reddish purple
+};
+
+// Themes available to the cycler, in order.
+const THEMES: [Theme; 2] = [CLASSIC_THEME, COLORBLIND_THEME];
+
+// Heatmap gradient for live cells, indexed by live-neighbor count (0-8).
+// Cool colors are isolated/dying cells, warm colors are crowded ones.
+const HEATMAP_COLORS: [Color; 9] = [
+    Color::Blue,
+    Color::LightBlue,
+    Color::Cyan,
+    Color::Green,
+    Color::LightGreen,
+    Color::Yellow,
+    Color::LightRed,
+    Color::Red,
+    Color::Magenta,
+];
+
+// Colors for live cells by age (generations continuously alive), each used from its
+// threshold up to the next. Fresh births are bright and hot, long-lived cells cool.
+const AGE_COLORS: [(u32, Color); 8] = [
+    (0, Color::White),
+    (1, Color::LightYellow),
+    (2, Color::Yellow),
+    (3, Color::LightRed),
+    (5, Color::Red),
+    (10, Color::Magenta),
+    (20, Color::LightBlue),
+    (50, Color::Blue),
+];
+
+const USAGE: &str = "\
+Usage: conway_game_of_rust [OPTIONS] [FILE]
+
+Arguments:
+  [FILE]         Pattern (.rle, .cells) or saved board to start with
+
+Options:
+  --width <N>    Board width in cells (default 128, or wider to fit FILE)
+  --height <N>   Board height in cells (default 80, or taller to fit FILE)
+  --wrap         Wrap around the edges (toroidal board)
+  --history <N>  Snapshots kept for undo and for stepping back (default 100)
+  --restore      Start with the board as it was when the app last quit
+  --soup-search  Run random soups without the interface and print how they end
+  --seeds <A..B> Seeds of the soups to run (default 0..100)
+  --generations <N>
+                 Generations to run each soup for (default 500)
+  -q, --quiet    Don't print the session summary on exit
+  -h, --help     Print this help";
+
+/// Settings taken from the command line.
+struct Options {
+    quiet: bool,           // Suppress the session summary, for scripting
+    width: Option<usize>,  // None to use the default, or fit the starting file
+    height: Option<usize>, // Likewise
+    wrap: bool,
+    history: Option<usize>, // Cap on undo and step-back snapshots, None for the default
+    restore: bool,          // Start from the board autosaved on the last exit
+    file: Option<String>,   // Pattern or board to load at startup
+    // Run soups headless instead of starting the app
+    soup_search: Option<SoupSearch>,
+}
+
+/// A batch of seeded random soups, each run for the same number of generations
+/// and classified by how it ended. The same seeds always give the same tally.
+struct SoupSearch {
+    seeds: Range<u64>,
+    generations: u64,
+}
+
+impl Options {
+    /// Parses the arguments after the program name. Both `--width 64` and
+    /// `--width=64` are accepted, while a file name containing `=` is left whole.
+    /// `Ok(None)` means `--help` was asked for.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+        let mut options = Options {
+            quiet: false,
+            width: None,
+            height: None,
+            wrap: false,
+            history: None,
+            restore: false,
+            file: None,
+            soup_search: None,
+        };
+        let (mut soup_search, mut seeds, mut generations) = (false, None, None);
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let no_value = |name: &str| match &inline_value {
+                Some(_) => Err(format!("{name} doesn't take a value")),
+                None => Ok(()),
+            };
+            let mut value = |name: &str| {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} needs a value"))
+            };
+            let size = |name: &str, value: String| {
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("{name} must be a positive number, got `{value}`"))
+            };
+
+            match flag.as_str() {
+                "--width" => options.width = Some(size("--width", value("--width")?)?),
+                "--height" => options.height = Some(size("--height", value("--height")?)?),
+                "--history" => {
+                    let value = value("--history")?;
+                    let limit = value.parse().map_err(|_| {
+                        format!("--history must be a number of snapshots, got `{value}`")
+                    })?;
+                    options.history = Some(limit);
+                }
+                "--wrap" => {
+                    no_value("--wrap")?;
+                    options.wrap = true;
+                }
+                "--restore" => {
+                    no_value("--restore")?;
+                    options.restore = true;
+                }
+                "--soup-search" => {
+                    no_value("--soup-search")?;
+                    soup_search = true;
+                }
+                "--seeds" => {
+                    let value = value("--seeds")?;
+                    let range = value
+                        .split_once("..")
+                        .and_then(|(start, end)| Some(start.parse().ok()?..end.parse().ok()?))
+                        .filter(|range: &Range<u64>| !range.is_empty())
+                        .ok_or_else(|| {
+                            format!("--seeds must be a range like 0..1000, got `{value}`")
+                        })?;
+                    seeds = Some(range);
+                }
+                "--generations" => {
+                    let value = value("--generations")?;
+                    let count = value.parse().map_err(|_| {
+                        format!("--generations must be a number of generations, got `{value}`")
+                    })?;
+                    generations = Some(count);
+                }
+                "-q" | "--quiet" => {
+                    no_value("--quiet")?;
+                    options.quiet = true;
+                }
+                "-h" | "--help" => {
+                    no_value("--help")?;
+                    return Ok(None);
+                }
+                _ if flag.starts_with('-') => return Err(format!("unknown argument `{flag}`")),
+                _ if options.file.is_some() => return Err(format!("unexpected argument `{flag}`")),
+                _ => options.file = Some(flag),
+            }
+        }
+        if options.restore && options.file.is_some() {
+            return Err("--restore can't be combined with a starting file".to_string());
+        }
+        if soup_search {
+            if options.restore || options.file.is_some() {
+                return Err("--soup-search starts from random soups, not a board".to_string());
+            }
+            options.soup_search = Some(SoupSearch {
+                seeds: seeds.unwrap_or(0..100),
+                generations: generations.unwrap_or(500),
+            });
+        } else if seeds.is_some() || generations.is_some() {
+            return Err("--seeds and --generations only apply to --soup-search".to_string());
+        }
+        Ok(Some(options))
+    }
+
+    /// Builds the starting grid, rejecting sizes too large to allocate. A starting file
+    /// is centered on the board, which grows past the default size to fit it unless
+    /// `--width` / `--height` say otherwise. A saved board keeps its own size by default.
+    ///
+    /// Also returns a note for the status line if the file had to be clipped to fit
+    /// the size asked for.
+    ///
+    /// With `--restore` the board autosaved at `session` is loaded instead. If there
+    /// isn't one, or it can't be read, the app starts with the default board and the
+    /// note says why.
+    fn grid(&self, session: Option<&Path>) -> Result<(Grid, Option<String>), String> {
+        if !self.restore {
+            return self.grid_from(self.file.as_deref());
+        }
+        let restored = match session {
+            Some(path) if path.is_file() => self.grid_from(Some(&path.to_string_lossy())),
+            Some(_) => Err("no session saved yet".to_string()),
+            None => Err("no directory to keep it in, set $XDG_DATA_HOME or $HOME".to_string()),
+        };
+        restored.or_else(|err| {
+            let (grid, _) = self.grid_from(None)?;
+            Ok((
+                grid,
+                Some(format!("Couldn't restore the last session: {err}")),
+            ))
+        })
+    }
+
+    /// Builds the starting grid from `file`, or an empty one, as described for `grid`.
+    fn grid_from(&self, file: Option<&str>) -> Result<(Grid, Option<String>), String> {
+        let default_grid = Grid::default();
+        let (mut grid, note) = match file {
+            None => {
+                let grid = Grid::try_new(
+                    self.width.unwrap_or(default_grid.width),
+                    self.height.unwrap_or(default_grid.height),
+                )
+                .map_err(|err| err.to_string())?;
+                (grid, None)
+            }
+            Some(path) => {
+                let loaded =
+                    read_board_file(path).map_err(|err| format!("can't load {path}: {err}"))?;
+                let (fit_width, fit_height) = match FileFormat::of(path) {
+                    FileFormat::Board => (loaded.width, loaded.height),
+                    FileFormat::Rle | FileFormat::Plaintext => (
+                        loaded.width.max(default_grid.width),
+                        loaded.height.max(default_grid.height),
+                    ),
+                };
+                // Only an explicit size can be too small, and it's kept as asked
+                fit_pattern(
+                    &loaded,
+                    self.width.unwrap_or(fit_width),
+                    self.height.unwrap_or(fit_height),
+                    LoadFit::Clip,
+                )?
+            }
+        };
+        if self.wrap {
+            grid.set_boundary_mode(BoundaryMode::Wrap);
+        }
+        Ok((grid, note))
+    }
+}
+
+impl SoupSearch {
+    /// Runs every soup on a board like the one the app would start with, and returns
+    /// a table of how many ended each way, with oscillators split by period and
+    /// spaceships by kind. A board that's still changing counts as chaotic.
+    fn run(&self, options: &Options) -> Result<String, String> {
+        // Keyed by the order rows are listed in, then the period, then the label
+        let mut tally: BTreeMap<(u8, usize, String), usize> = BTreeMap::new();
+        let (mut width, mut height) = (0, 0);
+        for seed in self.seeds.clone() {
+            let (mut grid, _) = options.grid_from(None)?;
+            (width, height) = (grid.width, grid.height);
+            grid.randomize(RANDOM_DENSITY, seed);
+            grid.step_n(self.generations);
+            let key = match grid.classify() {
+                Classification::Extinct => (0, 0, "extinct".to_string()),
+                Classification::StillLife => (1, 0, "still life".to_string()),
+                Classification::Oscillator { period } => {
+                    (2, period, format!("period-{period} oscillator"))
+                }
+                Classification::Spaceship(spaceship) => (3, 0, spaceship.name().to_string()),
+                Classification::Chaotic => (4, 0, "chaotic".to_string()),
+            };
+            *tally.entry(key).or_default() += 1;
+        }
+
+        let total = self.seeds.end - self.seeds.start;
+        let mut table = format!(
+            "Soup search: seeds {}..{}, {} generations each on a {width}x{height} board\n",
+            self.seeds.start, self.seeds.end, self.generations
+        );
+        for ((_, _, label), count) in tally {
+            let percent = count as f64 * 100.0 / total as f64;
+            table += &format!("{label:<20} {count:>8} {percent:>6.1}%\n");
+        }
+        table += &format!("{:<20} {total:>8}", "total");
+        Ok(table)
+    }
+}
+
+/// Reports a bad command line and exits with a non-zero status.
+fn exit_with_usage(err: &str) -> ! {
+    eprintln!("error: {err}\n\n{USAGE}");
+    std::process::exit(2);
+}
+
 fn main() -> io::Result<()> {
+    // Bad arguments print the usage and exit before the terminal is touched
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Err(err) => exit_with_usage(&err),
+    };
+    if let Some(search) = &options.soup_search {
+        match search.run(&options) {
+            Ok(table) => println!("{table}"),
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    let session = default_slot_dir().map(|dir| dir.join(SESSION_FILE));
+    let (grid, note) = options.grid(session.as_deref()).unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    });
+
     // Initialize the terminal interface (enters raw mode, clears screen)
     let mut terminal = ratatui::init();
+    execute!(io::stdout(), EnableMouseCapture)?;
     // Run the application loop
-    let app_result = App::default().run(&mut terminal);
+    let mut app = App {
+        grid,
+        status: note.unwrap_or_default(),
+        history: History::new(options.history.unwrap_or(HISTORY_LIMIT)),
+        ..App::default()
+    };
+    let app_result = app.run(&mut terminal);
     // Restore terminal to normal state (leaves raw mode) upon exit
+    execute!(io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
+
+    // Whatever was on the board is kept for `--restore`, even if it wasn't asked for this time
+    if let Some(path) = &session {
+        if let Err(err) = save_session(path, &app.grid) {
+            eprintln!(
+                "warning: can't save the session to {}: {err}",
+                path.display()
+            );
+        }
+    }
+
+    if !options.quiet {
+        println!("{}", app.stats.summary(app.grid.population()));
+    }
     app_result
 }
 
+/// Running totals for the session, printed as a one-line summary on quit.
+struct SessionStats {
+    started: Instant,
+    generations: u64,
+    peak_population: usize,
+}
+
+impl SessionStats {
+    fn new() -> Self {
+        SessionStats {
+            started: Instant::now(),
+            generations: 0,
+            peak_population: 0,
+        }
+    }
+
+    /// Records the current population, keeping track of the highest seen.
+    fn record_population(&mut self, population: usize) {
+        self.peak_population = self.peak_population.max(population);
+    }
+
+    fn summary(&self, final_population: usize) -> String {
+        let elapsed = self.started.elapsed().as_secs();
+        format!(
+            "Simulated {} generations | peak population {} | final population {} | session {}m{:02}s",
+            self.generations,
+            self.peak_population,
+            final_population,
+            elapsed / 60,
+            elapsed % 60
+        )
+    }
+}
+
+/// Snapshots of the grid from before each edit, for undo, and of the states undone
+/// since, for redo. Only cells and dimensions are kept; settings such as the boundary
+/// mode aren't part of an edit and stay as they are.
+struct History {
+    undo: VecDeque<GridSnapshot>, // Oldest first, at most `limit` long
+    redo: Vec<GridSnapshot>,      // Most recently undone last
+    limit: usize,                 // Most snapshots kept for undo
+}
+
+impl History {
+    fn new(limit: usize) -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Changes how many edits can be undone, dropping the oldest ones over the limit.
+    /// Redo is capped the same way, keeping the states undone most recently.
+    fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        trim_front(&mut self.undo, limit);
+        let excess = self.redo.len().saturating_sub(limit);
+        self.redo.drain(..excess);
+    }
+
+    /// Heap memory taken by the undo and redo snapshots, in bytes.
+    fn byte_size(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(&self.redo)
+            .map(GridSnapshot::byte_size)
+            .sum()
+    }
+
+    /// Saves `grid` as it was before an edit. A new edit starts a new timeline, so
+    /// anything that was undone can no longer be redone.
+    fn record(&mut self, grid: &Grid) {
+        self.record_snapshot(grid.snapshot());
+    }
+
+    /// Like `record`, for a snapshot taken before the edit was attempted.
+    fn record_snapshot(&mut self, snapshot: GridSnapshot) {
+        self.undo.push_back(snapshot);
+        trim_front(&mut self.undo, self.limit);
+        self.redo.clear();
+    }
+
+    /// Steps `grid` back one edit, keeping its current state for redo. Returns false
+    /// if there's nothing to undo.
+    fn undo(&mut self, grid: &mut Grid) -> bool {
+        let Some(previous) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(grid.snapshot());
+        grid.restore(&previous);
+        true
+    }
+
+    /// Reapplies the last undone edit to `grid`, keeping its current state for undo.
+    /// Returns false if there's nothing to redo.
+    fn redo(&mut self, grid: &mut Grid) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(grid.snapshot());
+        trim_front(&mut self.undo, self.limit);
+        grid.restore(&next);
+        true
+    }
+}
+
 /// The main application state.
 /// This struct holds the "Model" (Grid) and the "Controller" state (cursor, modes).
-#[derive(Default)]
 pub struct App {
     grid: Grid,
     cursor_pos: (usize, usize), // Current (row, col) of the user's cursor
     selection_anchor: Option<(usize, usize)>, // Where the user started their visual selection (if any)
-    mode: Mode,                               // Current input mode (Normal, Visual, Running)
-    exit: bool,                               // Flag to break the main loop
+    pinned_region: Option<(usize, usize, usize, usize)>, // Region kept for analysis across generations
+    mode: Mode,                          // Current input mode (Normal, Visual, Running)
+    tick_ms: u64,                        // Milliseconds between generations while RUNNING
+    disk_radius: usize,                  // Radius used by the disk stamp tool
+    pattern: NamedPattern,               // Library pattern placed by the insert key
+    load_fit: LoadFit,                   // What `:e` does with a pattern bigger than the board
+    pause_on_loop: bool,                 // Pause a run once the board repeats an earlier state
+    auto_expand: bool,                   // Grow the board when live cells near an edge
+    auto_backend: bool,                  // Switch between dense and sparse cells by density
+    watch: Option<Watch>,                // Population threshold reported when first met
+    symmetry: Symmetry,                  // Mirror axes applied to single-cell toggles
+    render_style: RenderStyle,           // How grid cells map onto terminal characters
+    theme_index: usize,                  // Index into THEMES of the active color theme
+    heatmap: bool,                       // Color live cells by their neighbor count
+    neighbor_counts: Option<Vec<usize>>, // Cached counts for the heatmap, None when stale
+    preview: bool,                       // Tint cells about to be born or die while paused
+    age_colors: bool,                    // Color live cells by how long they've been alive
+    next_states: Option<Vec<CellState>>, // Cached next generation for the preview, None when stale
+    entropy: Option<(u64, f64)>,         // Block entropy and the grid revision it was computed at
+    history: History,                    // Grids before each edit, for undo/redo
+    past_generations: VecDeque<GridSnapshot>, // Cells before each generation, for stepping back
+    clipboard: Option<CellBlock>,        // Last region yanked in VISUAL mode, for pasting
+    system_clipboard: fn(&str) -> io::Result<()>, // Puts text on the system clipboard
+    run_for: Option<(usize, usize)>,     // Generations left and asked for by `:runfor`
+    fast_forward: Option<FastForward>,   // A `:run` still computing, if any
+    slot_dir: Option<PathBuf>,           // Where named slots are kept, None without a home
+    slot_menu: Option<SlotMenu>,         // The `:load` menu, while it's open
+    pending_keys: String, // Partially typed key sequence, empty when none is in progress
+    pending_count: Option<usize>, // Count typed before a movement key (the 5 in `5j`)
+    pending_since: Option<Instant>, // When the pending sequence was started, for the timeout
+    command: String,      // Text typed at the `:` prompt in COMMAND mode
+    completion: Option<Completion>, // Matches offered by the last Tab at the prompt
+    status: String,       // Result of the last command, shown until the next key press
+    show_help: bool,      // Whether the key binding overlay is open
+    help_scroll: u16,     // Lines the help overlay is scrolled down by
+    scroll_row: usize,    // Topmost grid row in view, for grids taller than the terminal
+    scroll_col: usize,    // Leftmost grid column in view, for grids wider than the terminal
+    last_area: Rect,      // Terminal area of the last frame, for mouse mapping
+    drag: Option<Drag>,   // Mouse button held down over the grid, if any
+    line_cache: RefCell<LineCache>, // Grid lines from the last frame, reused when unchanged
+    stats: SessionStats,  // Totals reported when the app exits
+    exit: bool,           // Flag to break the main loop
+}
+
+impl Default for App {
+    fn default() -> Self {
+        App {
+            grid: Grid::default(),
+            cursor_pos: (0, 0),
+            selection_anchor: None,
+            pinned_region: None,
+            mode: Mode::default(),
+            tick_ms: TIME_BETWEEN_GENERATIONS,
+            disk_radius: DEFAULT_DISK_RADIUS,
+            pattern: NamedPattern::Glider,
+            load_fit: LoadFit::default(),
+            pause_on_loop: false,
+            auto_expand: false,
+            auto_backend: true,
+            watch: None,
+            symmetry: Symmetry::default(),
+            render_style: RenderStyle::default(),
+            theme_index: 0,
+            heatmap: false,
+            neighbor_counts: None,
+            preview: false,
+            age_colors: false,
+            next_states: None,
+            entropy: None,
+            history: History::new(HISTORY_LIMIT),
+            past_generations: VecDeque::new(),
+            clipboard: None,
+            system_clipboard: copy_to_system_clipboard,
+            run_for: None,
+            fast_forward: None,
+            slot_dir: default_slot_dir(),
+            slot_menu: None,
+            pending_keys: String::new(),
+            pending_count: None,
+            pending_since: None,
+            command: String::new(),
+            completion: None,
+            status: String::new(),
+            show_help: false,
+            help_scroll: 0,
+            scroll_row: 0,
+            scroll_col: 0,
+            last_area: Rect::default(),
+            drag: None,
+            line_cache: RefCell::default(),
+            stats: SessionStats::new(),
+            exit: false,
+        }
+    }
+}
+
+/// A pattern saved with `:save <name>`, as listed in the `:load` menu.
+struct Slot {
+    name: String,
+    width: usize,  // Size of the saved pattern, trimmed to its live cells
+    height: usize, // Likewise
+}
+
+/// The popup opened by a bare `:load`, listing the saved slots.
+struct SlotMenu {
+    slots: Vec<Slot>, // Sorted by name, never empty
+    selected: usize,  // Index of the highlighted slot
+}
+
+/// Progress through a `:run <n>`, which computes generations without drawing them,
+/// a time-boxed chunk per frame.
+struct FastForward {
+    done: u64,               // Generations computed so far
+    total: u64,              // Generations asked for
+    watched: Option<String>, // What `:watch` reported along the way, shown at the end
+}
+
+/// What loading a pattern does when it's bigger than the board, set with `:set fit`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+enum LoadFit {
+    #[default]
+    Resize, // Grow the board until the pattern fits
+    Clip, // Keep the board's size and drop the cells that fall outside it
+}
+
+impl Display for LoadFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadFit::Resize => write!(f, "resize"),
+            LoadFit::Clip => write!(f, "clip"),
+        }
+    }
+}
+
+/// A population threshold set with `:watch`, checked every generation until it's
+/// first met.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Watch {
+    comparison: Comparison,
+    threshold: usize,
+    pause: bool, // Pause the run as well as reporting it
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Comparison {
+    Above, // `>`
+    Below, // `<`
+}
+
+impl Watch {
+    fn is_met(&self, population: usize) -> bool {
+        match self.comparison {
+            Comparison::Above => population > self.threshold,
+            Comparison::Below => population < self.threshold,
+        }
+    }
+
+    /// The status line once `grid` has met the threshold, e.g.
+    /// "Population first went over 1000 at gen 47 (1012)".
+    fn report(&self, grid: &Grid) -> String {
+        let crossed = match self.comparison {
+            Comparison::Above => "went over",
+            Comparison::Below => "fell under",
+        };
+        format!(
+            "Population first {crossed} {} at gen {} ({})",
+            self.threshold,
+            grid.generation(),
+            grid.population()
+        )
+    }
+}
+
+/// As typed after `:watch`, for example "pop > 1000".
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let comparison = match self.comparison {
+            Comparison::Above => '>',
+            Comparison::Below => '<',
+        };
+        write!(f, "pop {comparison} {}", self.threshold)?;
+        if self.pause {
+            write!(f, " pause")?;
+        }
+        Ok(())
+    }
+}
+
+/// A mouse press in progress over the grid.
+struct Drag {
+    button: MouseButton,  // Left paints Alive, right paints Dead
+    last: (usize, usize), // Last cell painted (or pressed, before any movement)
+    moved: bool,          // False until the pointer leaves the pressed cell
 }
 
 /// Represents the current state of the interface.
@@ -46,11 +901,15 @@ pub struct App {
 /// - NORMAL: Move cursor, toggle single cells.
 /// - VISUAL: Select multiple cells to toggle at once.
 /// - RUNNING: The simulation is active and updating.
-#[derive(PartialEq)]
+/// - COMMAND: Typing an Ex-style command at the `:` prompt.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Default)]
 enum Mode {
     RUNNING,
+    #[default]
     NORMAL,
     VISUAL,
+    COMMAND,
 }
 
 // Display trait allows us to easily print the mode into the title bar
@@ -60,14 +919,86 @@ impl Display for Mode {
             Self::NORMAL => "[NORMAL]",
             Self::RUNNING => "[RUNNING]",
             Self::VISUAL => "[VISUAL]",
+            Self::COMMAND => "[COMMAND]",
         };
         write!(f, "{mode_str}")
     }
 }
 
-impl Default for Mode {
-    fn default() -> Self {
-        Mode::NORMAL
+/// Which axes a single-cell toggle is mirrored across, for drawing symmetric patterns.
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Symmetry {
+    #[default]
+    Off,
+    Vertical,   // Mirror across the vertical center axis (left <-> right)
+    Horizontal, // Mirror across the horizontal center axis (top <-> bottom)
+    Both,       // 4-fold symmetry
+}
+
+impl Symmetry {
+    /// Cycles Off -> Vertical -> Horizontal -> Both -> Off.
+    fn next(self) -> Self {
+        match self {
+            Symmetry::Off => Symmetry::Vertical,
+            Symmetry::Vertical => Symmetry::Horizontal,
+            Symmetry::Horizontal => Symmetry::Both,
+            Symmetry::Both => Symmetry::Off,
+        }
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symmetry_str = match self {
+            Self::Off => "",
+            Self::Vertical => "[MIRROR |]",
+            Self::Horizontal => "[MIRROR -]",
+            Self::Both => "[MIRROR +]",
+        };
+        write!(f, "{symmetry_str}")
+    }
+}
+
+/// How each grid cell is drawn onto the terminal.
+/// - Standard: one text row per grid row, two columns (`██`) per cell.
+/// - DoubledRows: each grid row is drawn on two text rows, so cells look closer to
+///   square on terminals with wide fonts. Halves the number of visible rows.
+/// - HalfBlock: each text row shows two grid rows using `▀` with fg/bg colors, and
+///   each cell is one column wide. Doubles the visible rows and columns.
+#[derive(Clone, Copy, PartialEq, Default)]
+enum RenderStyle {
+    #[default]
+    Standard,
+    DoubledRows,
+    HalfBlock,
+}
+
+impl RenderStyle {
+    /// Cycles through the available render styles.
+    fn next(self) -> Self {
+        match self {
+            RenderStyle::Standard => RenderStyle::DoubledRows,
+            RenderStyle::DoubledRows => RenderStyle::HalfBlock,
+            RenderStyle::HalfBlock => RenderStyle::Standard,
+        }
+    }
+
+    /// Number of terminal columns used to draw a single cell.
+    fn cell_columns(self) -> u16 {
+        match self {
+            RenderStyle::Standard | RenderStyle::DoubledRows => 2,
+            RenderStyle::HalfBlock => 1,
+        }
+    }
+
+    /// Maps a text row (relative to the top of the grid) to the grid row drawn there.
+    /// Half-block rows hold two grid rows; the upper one is returned.
+    fn grid_row_at(self, text_row: usize) -> usize {
+        match self {
+            RenderStyle::Standard => text_row,
+            RenderStyle::DoubledRows => text_row / 2,
+            RenderStyle::HalfBlock => text_row * 2,
+        }
     }
 }
 
@@ -75,109 +1006,649 @@ impl App {
     /// The main event loop.
     /// This handles drawing, input polling, and updating the simulation state.
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        let tick_rate = Duration::from_millis(TIME_BETWEEN_GENERATIONS);
         let mut last_tick = Instant::now();
 
         while !self.exit {
+            // Read every frame so speed changes take effect immediately
+            let tick_rate = Duration::from_millis(self.tick_ms);
+
             // 1. Render the current state
+            self.refresh_neighbor_counts();
+            self.refresh_next_states();
+            self.sync_age_tracking();
+            self.refresh_entropy();
             terminal.draw(|frame| self.draw(frame))?;
 
-            // 2. Calculate remaining time in this frame to maintain consistent speed
-            let timeout = tick_rate
-                .checked_sub(last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
+            // 2. Calculate remaining time in this frame to maintain consistent speed.
+            // A `:run` in progress only checks for input before its next chunk.
+            let timeout = if self.fast_forward.is_some() {
+                Duration::ZERO
+            } else {
+                tick_rate
+                    .checked_sub(last_tick.elapsed())
+                    .unwrap_or_else(|| Duration::from_secs(0))
+            };
 
             // 3. Poll for user input (non-blocking wait based on timeout)
             if event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
+                match event::read()? {
                     // Only handle press events, ignore release/repeat for cleaner input
-                    if key.kind == KeyEventKind::Press {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
                         self.handle_key_event(key);
+                        self.invalidate_overlays();
+                    }
+                    Event::Mouse(mouse) => {
+                        self.handle_mouse_event(mouse);
+                        self.invalidate_overlays();
                     }
+                    _ => {}
                 }
             }
 
             // 4. Update the simulation if the timer has elapsed and we are RUNNING
-            if last_tick.elapsed() >= tick_rate {
+            // Ticks are scheduled at fixed intervals from the previous tick rather than from
+            // when it was handled, so overshoot doesn't accumulate and slow the simulation.
+            let mut ticks = 0;
+            while last_tick.elapsed() >= tick_rate && ticks < MAX_CATCH_UP_TICKS {
                 if self.mode == Mode::RUNNING {
-                    self.grid.next_generation();
+                    self.tick();
                 }
+                last_tick += tick_rate;
+                ticks += 1;
+            }
+            if last_tick.elapsed() >= tick_rate {
                 last_tick = Instant::now();
             }
+
+            // 5. Compute the next chunk of a `:run`
+            self.run_chunk();
+
+            // 6. Forget half-typed key sequences that were abandoned
+            self.expire_pending_keys();
+
+            // 7. Track peak population across edits (generations record their own, since
+            // several can run in one frame)
+            self.stats.record_population(self.grid.population());
         }
         Ok(())
     }
 
-    /// Helper to bridge the App struct with Ratatui's widget system
-    fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
-    }
-
-    /// Handles all keyboard inputs.
-    /// This acts as the "Controller," modifying state based on key codes.
-    fn handle_key_event(&mut self, key_event: KeyEvent) {
-        let (row, col) = self.cursor_pos;
-
-        match key_event.code {
-            // --- GLOBAL KEYS (Always Work) ---
-            KeyCode::Char('q') => self.exit(),
-            // Enter acts as the Play/Pause toggle
-            KeyCode::Enter => {
-                if self.mode == Mode::RUNNING {
-                    self.mode = Mode::NORMAL
-                } else {
-                    self.mode = Mode::RUNNING
-                }
+    /// Advances one generation of a run, pausing once nothing will change from here on,
+    /// a `:runfor` countdown reaches zero or, with `:set loop on`, the board starts
+    /// repeating.
+    fn tick(&mut self) {
+        self.advance_generation();
+        self.invalidate_overlays();
+        if self.check_watch() {
+            self.run_for = None;
+            self.mode = Mode::NORMAL;
+        }
+        if self.pause_on_loop {
+            // Checked every generation, so the first match is where the loop began.
+            // Period 1 is a still life, which `is_stable` below already pauses on.
+            if let Some(period) = self.grid.detect_cycle(CYCLE_HISTORY_LEN).filter(|&p| p > 1) {
+                let entered = self.grid.generation() - period as u64;
+                self.status = format!("Entered period-{period} loop at gen {entered}");
+                self.run_for = None;
+                self.mode = Mode::NORMAL;
             }
-            // Esc always returns to a safe "Normal" state
-            KeyCode::Esc => {
+        }
+        if let Some((left, total)) = &mut self.run_for {
+            *left -= 1;
+            if *left == 0 {
+                self.status = format!("Ran {total} generations");
+                self.run_for = None;
                 self.mode = Mode::NORMAL;
-                self.selection_anchor = None;
             }
+        }
+        if self.grid.is_stable() {
+            self.run_for = None;
+            self.mode = Mode::NORMAL;
+        }
+    }
 
-            // --- MODE SWITCHING ---
-            // 'v' enters Visual Mode (unless simulation is running)
-            KeyCode::Char('v') if self.mode != Mode::RUNNING => {
-                self.mode = Mode::VISUAL;
-                self.selection_anchor = Some((row, col));
+    /// Computes as much of a `:run` as fits in `RUN_CHUNK_BUDGET`, finishing it once
+    /// every generation asked for is done.
+    fn run_chunk(&mut self) {
+        // Taken out of `self` while it's worked on, and put back unless it finishes
+        let Some(mut run) = self.fast_forward.take() else {
+            return;
+        };
+        let started = Instant::now();
+        let mut paused = false;
+        while !paused && run.done < run.total && started.elapsed() < RUN_CHUNK_BUDGET {
+            // A settled board can skip straight to the end
+            let steps = if self.grid.is_stable() {
+                run.total - run.done
+            } else {
+                1
+            };
+            self.expand_near_edges();
+            self.grid.step_n(steps);
+            self.stats.record_population(self.grid.population());
+            self.pick_backend();
+            run.done += steps;
+            self.stats.generations += steps;
+            // Reported when the run ends, since its own status would replace this one
+            if let Some(watch) = self.watch.filter(|w| w.is_met(self.grid.population())) {
+                run.watched = Some(watch.report(&self.grid));
+                self.watch = None;
+                paused = watch.pause;
             }
+        }
+        if paused || run.done == run.total {
+            self.status = match (run.watched.take(), paused) {
+                (Some(report), true) => report,
+                (Some(report), false) => format!("Ran {} generations. {report}", run.total),
+                (None, _) => format!("Ran {} generations", run.total),
+            };
+        } else {
+            self.fast_forward = Some(run);
+        }
+        self.invalidate_overlays();
+    }
 
-            // --- MOVEMENT (Works in NORMAL and VISUAL mode) ---
-            // Supports both Vim keys (hjkl) and Arrow keys.
-            // Guarded by `if self.mode != Mode::RUNNING` to prevent cursor interference during sim.
-            KeyCode::Left | KeyCode::Char('h') if self.mode != Mode::RUNNING => {
-                if col > 0 {
-                    self.cursor_pos.1 -= 1;
-                }
-            }
-            KeyCode::Down | KeyCode::Char('j') if self.mode != Mode::RUNNING => {
-                if row < self.grid.height - 1 {
-                    self.cursor_pos.0 += 1
-                }
-            }
-            KeyCode::Up | KeyCode::Char('k') if self.mode != Mode::RUNNING => {
-                if row > 0 {
-                    self.cursor_pos.0 -= 1
-                }
-            }
-            KeyCode::Right | KeyCode::Char('l') if self.mode != Mode::RUNNING => {
-                if col < self.grid.width - 1 {
-                    self.cursor_pos.1 += 1;
-                }
-            }
+    /// Reports the generation the `:watch` threshold was first met at, if it just
+    /// was, and stops watching. True if the watch asked for the run to pause there.
+    fn check_watch(&mut self) -> bool {
+        let Some(watch) = self.watch.filter(|w| w.is_met(self.grid.population())) else {
+            return false;
+        };
+        self.status = watch.report(&self.grid);
+        self.watch = None;
+        watch.pause
+    }
 
-            // --- ACTIONS ---
-            // 'r' to reset (clear) the board
-            KeyCode::Char('r') => {
-                if self.mode != Mode::RUNNING {
-                    self.grid.reset();
-                }
+    /// Computes the next generation, keeping the current one for stepping back.
+    fn advance_generation(&mut self) {
+        self.expand_near_edges();
+        self.past_generations.push_back(self.grid.snapshot());
+        trim_front(&mut self.past_generations, self.history.limit);
+        self.grid.next_generation();
+        self.pick_backend();
+        self.stats.generations += 1;
+        self.stats.record_population(self.grid.population());
+    }
+
+    /// With `:backend auto`, moves the cells to whichever backend suits the board's
+    /// density, by the `SPARSE_BELOW` and `DENSE_ABOVE` thresholds.
+    fn pick_backend(&mut self) {
+        if !self.auto_backend {
+            return;
+        }
+        let density = self.grid.density();
+        match self.grid.backend() {
+            Backend::Dense if density < SPARSE_BELOW => self.grid.set_backend(Backend::Sparse),
+            Backend::Sparse if density > DENSE_ABOVE => self.grid.set_backend(Backend::Dense),
+            _ => {}
+        }
+    }
+
+    /// With `:set expand on`, adds `EXPAND_STEP` dead rows or columns at each edge a
+    /// live cell is within `EXPAND_MARGIN` of, so nothing runs off the board. The
+    /// cursor, selection, pinned region and view move with the cells. Growth stops at
+    /// `MAX_EXPANDED_SIDE` on each side, past which expanding is switched off.
+    fn expand_near_edges(&mut self) {
+        if !self.auto_expand {
+            return;
+        }
+        let Some((min_r, max_r, min_c, max_c)) = self.grid.live_bounds() else {
+            return;
+        };
+        let (width, height) = (self.grid.width, self.grid.height);
+        let near = |distance: usize| distance < EXPAND_MARGIN;
+        let wanted = [
+            near(min_r),
+            near(height - 1 - max_r),
+            near(min_c),
+            near(width - 1 - max_c),
+        ];
+        if !wanted.contains(&true) {
+            return;
+        }
+        // Split what room is left between the two edges of an axis
+        let room = |len: usize, before: bool, after: bool| {
+            let spare = MAX_EXPANDED_SIDE.saturating_sub(len);
+            match (before, after) {
+                (true, true) => (
+                    EXPAND_STEP.min(spare / 2),
+                    EXPAND_STEP.min(spare - spare / 2),
+                ),
+                (true, false) => (EXPAND_STEP.min(spare), 0),
+                (false, true) => (0, EXPAND_STEP.min(spare)),
+                (false, false) => (0, 0),
+            }
+        };
+        let (top, bottom) = room(height, wanted[0], wanted[1]);
+        let (left, right) = room(width, wanted[2], wanted[3]);
+        if top + bottom + left + right == 0 {
+            self.auto_expand = false;
+            self.status = format!(
+                "The board can't grow past {MAX_EXPANDED_SIDE}x{MAX_EXPANDED_SIDE}, stopped expanding"
+            );
+            return;
+        }
+        if let Err(err) = self.grid.grow(top, bottom, left, right) {
+            self.auto_expand = false;
+            self.status = format!("Can't expand the board: {err}");
+            return;
+        }
+        let (row, col) = self.cursor_pos;
+        self.cursor_pos = (row + top, col + left);
+        self.selection_anchor = self.selection_anchor.map(|(r, c)| (r + top, c + left));
+        self.pinned_region = self.pinned_region.map(|(min_r, max_r, min_c, max_c)| {
+            (min_r + top, max_r + top, min_c + left, max_c + left)
+        });
+        self.scroll_row += top;
+        self.scroll_col += left;
+        // Older generations are at the old size, and would jump back to it
+        self.past_generations.clear();
+        self.invalidate_overlays();
+    }
+
+    /// Recomputes the heatmap's neighbor counts if the heatmap is on and the cache
+    /// was invalidated. While paused and idle this keeps the counts from being
+    /// recomputed every frame.
+    fn refresh_neighbor_counts(&mut self) {
+        if self.heatmap && self.neighbor_counts.is_none() {
+            self.neighbor_counts = Some(self.grid.neighbor_counts());
+        }
+    }
+
+    /// Computes the next generation for the change preview if it's on, the
+    /// simulation is paused and the cache was invalidated. While running it's skipped,
+    /// so the preview never slows the simulation down.
+    fn refresh_next_states(&mut self) {
+        if self.preview && self.mode != Mode::RUNNING && self.next_states.is_none() {
+            self.next_states = Some(self.grid.preview_next());
+        }
+    }
+
+    /// Recomputes the status line's block entropy once the cells have changed. It
+    /// scans the whole board, so it's kept between frames while the grid is idle.
+    fn refresh_entropy(&mut self) {
+        let revision = self.grid.revision();
+        if self.entropy.is_none_or(|(cached, _)| cached != revision) {
+            self.entropy = Some((revision, self.grid.block_entropy(ENTROPY_BLOCK_SIZE)));
+        }
+    }
+
+    /// Keeps the grid tracking ages exactly while age colors are on. Checked every
+    /// frame, since undo, loading and stepping back swap in grids with their own setting.
+    fn sync_age_tracking(&mut self) {
+        if self.grid.age_tracking() != self.age_colors {
+            self.grid.set_age_tracking(self.age_colors);
+        }
+    }
+
+    /// Marks the heatmap counts and change preview as stale after the grid (or the
+    /// mode) may have changed.
+    fn invalidate_overlays(&mut self) {
+        self.neighbor_counts = None;
+        self.next_states = None;
+    }
+
+    /// Helper to bridge the App struct with Ratatui's widget system
+    fn draw(&mut self, frame: &mut Frame) {
+        self.last_area = frame.area();
+        self.scroll_to_cursor(grid_inner_area(frame.area()));
+        frame.render_widget(&*self, frame.area());
+    }
+
+    /// Handles mouse input. A left click moves the cursor to the clicked cell and toggles
+    /// it, mirrored like `<Space>`; a right click kills it. Dragging with the left button
+    /// held paints cells Alive along the path, and with the right button paints them Dead.
+    /// Presses outside the grid are ignored.
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        // The board can't be edited while a `:run` is computing it
+        if self.fast_forward.is_some() {
+            return;
+        }
+        if self.mode == Mode::RUNNING {
+            self.drag = None;
+            return;
+        }
+        let pos = self.terminal_to_grid(mouse_event.column, mouse_event.row);
+
+        match mouse_event.kind {
+            MouseEventKind::Down(button @ (MouseButton::Left | MouseButton::Right)) => {
+                if let Some(pos) = pos.map(|pos| self.pick_half_block_row(pos)) {
+                    // The whole stroke is undone at once
+                    self.history.record(&self.grid);
+                    self.cursor_pos = pos;
+                    self.drag = Some(Drag {
+                        button,
+                        last: pos,
+                        moved: false,
+                    });
+                }
+            }
+            MouseEventKind::Drag(_) => {
+                if let (Some(drag), Some((row, col))) = (self.drag.as_mut(), pos) {
+                    // Consecutive events can skip cells on a fast drag, so connect them with
+                    // a line. Only painting (never toggling) keeps revisited cells unchanged.
+                    if (row, col) != drag.last {
+                        let state = match drag.button {
+                            MouseButton::Right => CellState::Dead,
+                            _ => CellState::Alive,
+                        };
+                        let (last_r, last_c) = drag.last;
+                        self.grid.draw_line(last_r, last_c, row, col, state);
+                        drag.last = (row, col);
+                        drag.moved = true;
+                        self.cursor_pos = (row, col);
+                    }
+                }
+            }
+            MouseEventKind::Up(_) => {
+                // A press released without moving is a click
+                if let Some(drag) = self.drag.take().filter(|drag| !drag.moved) {
+                    let (row, col) = drag.last;
+                    match drag.button {
+                        MouseButton::Right => self.grid.set(row, col, CellState::Dead),
+                        _ => self.toggle_with_symmetry(row, col),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Maps a terminal position (as reported by mouse events) to the grid cell drawn
+    /// there, or None if it falls outside the grid.
+    ///
+    /// Mirrors the layout used by `render`: the grid sits inside a one-character border,
+    /// each line is placed by `grid_line_origin` (centered, or filling the width if it
+    /// doesn't fit), each cell spans `cell_columns()` terminal columns, and the view is
+    /// shifted by the scroll offsets.
+    fn terminal_to_grid(&self, col: u16, row: u16) -> Option<(usize, usize)> {
+        let area = self.last_area;
+        if area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT {
+            return None;
+        }
+
+        let inner = grid_inner_area(area);
+        if !inner.contains((col, row).into()) {
+            return None;
+        }
+
+        let cell_columns = self.render_style.cell_columns();
+        let (line_x, line_width) = self.grid_line_origin(inner);
+        if col < line_x || col >= line_x + line_width {
+            return None;
+        }
+
+        let grid_col = self.scroll_col + ((col - line_x) / cell_columns) as usize;
+        let grid_row = self.scroll_row + self.render_style.grid_row_at((row - inner.y) as usize);
+        if grid_row >= self.grid.height || grid_col >= self.grid.width {
+            return None;
+        }
+        Some((grid_row, grid_col))
+    }
+
+    /// Chooses between the two cells of a half-block character, which a click can't
+    /// tell apart: `terminal_to_grid` gives the upper one, and pressing the character
+    /// again while the cursor is on it picks the lower one instead. Further presses
+    /// keep alternating. Other render styles show one cell per character and pass
+    /// `pos` through.
+    fn pick_half_block_row(&self, pos: (usize, usize)) -> (usize, usize) {
+        let (row, col) = pos;
+        let lower_fits = row + 1 < self.grid.height;
+        if self.render_style == RenderStyle::HalfBlock && self.cursor_pos == pos && lower_fits {
+            (row + 1, col)
+        } else {
+            pos
+        }
+    }
+
+    /// Scrolls just far enough to bring the cursor into view, then clamps the offsets so
+    /// the view never runs past the grid. Grids that fit entirely are never scrolled.
+    fn scroll_to_cursor(&mut self, inner: Rect) {
+        self.scroll_with(inner, scroll_axis);
+    }
+
+    /// Scrolls so the cursor is in the middle of the view, like Vim's `zz`, as far as
+    /// the view can go without running past the grid.
+    fn center_on_cursor(&mut self, inner: Rect) {
+        self.scroll_with(inner, center_axis);
+    }
+
+    /// Sets both scroll offsets for a view of the grid in `inner`, each picked by
+    /// `place(offset, cursor, view, len)` along its axis.
+    fn scroll_with(&mut self, inner: Rect, place: fn(usize, usize, usize, usize) -> usize) {
+        let (row, col) = self.cursor_pos;
+
+        // Rows scroll by whole display rows, so half-block lines keep pairing rows (2i, 2i + 1)
+        let rows_per_line = match self.render_style {
+            RenderStyle::Standard | RenderStyle::DoubledRows => 1,
+            RenderStyle::HalfBlock => 2,
+        };
+        let view_lines = (inner.height as usize / self.lines_per_display_row()).max(1);
+        self.scroll_row = place(
+            self.scroll_row / rows_per_line,
+            row / rows_per_line,
+            view_lines,
+            self.display_rows(),
+        ) * rows_per_line;
+
+        let view_cols = ((inner.width / self.render_style.cell_columns()) as usize).max(1);
+        self.scroll_col = place(self.scroll_col, col, view_cols, self.grid.width);
+    }
+
+    /// Handles all keyboard inputs.
+    /// This acts as the "Controller," modifying state based on key codes.
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        // The last command's result stays up until the user does something else
+        self.status.clear();
+
+        // A `:run` in progress only listens for Esc, which stops it where it is, and 'q'
+        if let Some(run) = &self.fast_forward {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.status =
+                        format!("Stopped after {} of {} generations", run.done, run.total);
+                    self.fast_forward = None;
+                }
+                KeyCode::Char('q') => self.exit(),
+                _ => {}
+            }
+            return;
+        }
+
+        // The `:` prompt takes every key until it's submitted or cancelled
+        if self.mode == Mode::COMMAND {
+            self.handle_command_key(key_event);
+            return;
+        }
+
+        // The help overlay covers the grid, so it only listens for the keys that
+        // scroll or close it
+        if self.show_help {
+            match key_event.code {
+                KeyCode::Char('?') | KeyCode::Esc => self.show_help = false,
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.help_scroll = (self.help_scroll + 1).min(self.max_help_scroll());
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.help_scroll = self.help_scroll.saturating_sub(1);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        // Likewise the slot menu, which takes over until a slot is picked or it's closed
+        if let Some(menu) = &mut self.slot_menu {
+            match key_event.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    menu.selected = (menu.selected + 1).min(menu.slots.len() - 1);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    menu.selected = menu.selected.saturating_sub(1);
+                }
+                KeyCode::Enter => {
+                    let name = menu.slots[menu.selected].name.clone();
+                    self.slot_menu = None;
+                    self.status = self.run_command(Command::LoadSlot(name));
+                }
+                KeyCode::Esc | KeyCode::Char('q') => self.slot_menu = None,
+                _ => {}
+            }
+            return;
+        }
+
+        // Multi-key sequences get first look; a key that extends or completes one
+        // is consumed here and never reaches the single-key bindings below.
+        if self.handle_key_sequence(key_event) {
+            self.pending_count = None;
+            return;
+        }
+
+        // Digits build up a count for the next movement. A `0` with no count started
+        // is the jump-to-first-column motion instead.
+        if let KeyCode::Char(digit @ '0'..='9') = key_event.code {
+            if self.mode != Mode::RUNNING
+                && !key_event.modifiers.contains(KeyModifiers::ALT)
+                && (digit != '0' || self.pending_count.is_some())
+            {
+                let digit = digit as usize - '0' as usize;
+                let count = self.pending_count.unwrap_or(0);
+                self.pending_count = Some(count.saturating_mul(10).saturating_add(digit));
+                return;
+            }
+        }
+        // Any other key uses up the count (or discards it, if it isn't a movement)
+        let count = self.pending_count.take().unwrap_or(1);
+
+        let (row, col) = self.cursor_pos;
+
+        match key_event.code {
+            // --- GLOBAL KEYS (Always Work) ---
+            KeyCode::Char('q') => self.exit(),
+            // Enter acts as the Play/Pause toggle
+            // Starting a run records the board, so a whole run can be undone at once
+            KeyCode::Enter => {
+                if self.mode == Mode::RUNNING {
+                    self.mode = Mode::NORMAL;
+                    self.run_for = None;
+                } else {
+                    self.history.record(&self.grid);
+                    self.mode = Mode::RUNNING
+                }
+            }
+            // Esc always returns to a safe "Normal" state
+            KeyCode::Esc => {
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+                self.run_for = None;
+            }
+
+            // Alt+1..9 jumps to a speed preset. Plain digits are left free for counts.
+            KeyCode::Char(digit @ '1'..='9') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                let preset = digit as usize - '1' as usize;
+                self.tick_ms = SPEED_PRESETS[preset];
+            }
+
+            // '+' / '-' lengthen or shorten the interval between generations by a quarter,
+            // so each press feels about the same at any speed
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.tick_ms =
+                    (self.tick_ms + (self.tick_ms / 4).max(1)).clamp(MIN_TICK_MS, MAX_TICK_MS);
+            }
+            KeyCode::Char('-') => {
+                self.tick_ms = (self.tick_ms - self.tick_ms / 4).clamp(MIN_TICK_MS, MAX_TICK_MS);
+            }
+
+            // --- MODE SWITCHING ---
+            // 'v' enters Visual Mode (unless simulation is running)
+            KeyCode::Char('v') if self.mode != Mode::RUNNING => {
+                self.mode = Mode::VISUAL;
+                self.selection_anchor = Some((row, col));
+            }
+
+            // ':' opens the command prompt, dropping any visual selection
+            KeyCode::Char(':') if self.mode != Mode::RUNNING => {
+                self.mode = Mode::COMMAND;
+                self.selection_anchor = None;
+                self.command.clear();
+            }
+            // 'w' / 'e' open the prompt with a write / load command started, ready for a path
+            KeyCode::Char(key @ ('w' | 'e')) if self.mode == Mode::NORMAL => {
+                self.mode = Mode::COMMAND;
+                self.command = format!("{key} ");
+            }
+
+            // --- MOVEMENT (Works in NORMAL and VISUAL mode) ---
+            // Supports both Vim keys (hjkl) and Arrow keys, moving `count` cells and
+            // stopping at the edge of the grid.
+            // Guarded by `if self.mode != Mode::RUNNING` to prevent cursor interference during sim.
+            KeyCode::Left | KeyCode::Char('h') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.1 = col.saturating_sub(count);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.0 = row.saturating_add(count).min(self.grid.height - 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.0 = row.saturating_sub(count);
+            }
+            KeyCode::Right | KeyCode::Char('l') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.1 = col.saturating_add(count).min(self.grid.width - 1);
+            }
+            // Jumps to the edges: 'G' to the last row, '0' / '$' to the first / last column
+            // ('gg' for the first row is a key sequence)
+            KeyCode::Char('G') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.0 = self.grid.height - 1;
+            }
+            KeyCode::Char('0') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.1 = 0;
+            }
+            KeyCode::Char('$') if self.mode != Mode::RUNNING => {
+                self.cursor_pos.1 = self.grid.width - 1;
+            }
+
+            // --- ACTIONS ---
+            // 'u' undoes the last edit, Ctrl-r redoes it (checked before plain 'r' below)
+            KeyCode::Char('u') if self.mode != Mode::RUNNING => {
+                self.history.undo(&mut self.grid);
+                self.clamp_cursor();
+            }
+            KeyCode::Char('r')
+                if self.mode != Mode::RUNNING
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.history.redo(&mut self.grid);
+                self.clamp_cursor();
+            }
+            // 'n' advances exactly one generation and stays paused, for watching frame by frame
+            KeyCode::Char('n') if self.mode == Mode::NORMAL => {
+                self.history.record(&self.grid);
+                self.advance_generation();
+                self.check_watch();
+            }
+            // 'b' steps back to the generation before this one. Life can't be run in
+            // reverse, so this restores a stored copy; the step back itself can be undone.
+            KeyCode::Char('b') if self.mode == Mode::NORMAL => {
+                if let Some(previous) = self.past_generations.pop_back() {
+                    self.history.record(&self.grid);
+                    self.grid.restore(&previous);
+                    self.clamp_cursor();
+                }
+            }
+            // 'r' to reset (clear) the board
+            KeyCode::Char('r') if self.mode != Mode::RUNNING => {
+                self.history.record(&self.grid);
+                self.grid.reset();
+            }
+            KeyCode::Char('R') if self.mode != Mode::RUNNING => {
+                self.history.record(&self.grid);
+                self.grid.randomize(RANDOM_DENSITY, random_seed());
             }
             // Spacebar behavior changes based on context
             KeyCode::Char(' ') => match self.mode {
                 Mode::NORMAL => {
-                    // Simple toggle of the cell under cursor
-                    self.grid.toggle_cell(row, col);
+                    // Toggle the cell under cursor, plus any mirrored copies
+                    self.history.record(&self.grid);
+                    self.toggle_with_symmetry(row, col);
                 }
                 Mode::VISUAL => {
                     // Bulk toggle: flip all cells in the selected rectangle
@@ -185,6 +1656,7 @@ impl App {
                         let (min_r, max_r, min_c, max_c) =
                             get_row_and_col_span(row, col, anchor_r, anchor_c);
 
+                        self.history.record(&self.grid);
                         self.grid.multi_toggle_cells(min_r, max_r, min_c, max_c);
                     }
 
@@ -192,143 +1664,2998 @@ impl App {
                     self.mode = Mode::NORMAL;
                     self.selection_anchor = None;
                 }
-                Mode::RUNNING => {} // Do nothing while running
+                Mode::RUNNING | Mode::COMMAND => {} // Do nothing while running
             },
-            _ => {}
-        }
-    }
-
-    fn exit(&mut self) {
-        self.exit = true;
-    }
-}
-
-/// Helper function to calculate the bounding box of a selection.
-/// Takes two corners (cursor and anchor) and returns (min_row, max_row, min_col, max_col).
-fn get_row_and_col_span(
-    cursor_r: usize,
-    cursor_c: usize,
-    anchor_r: usize,
-    anchor_c: usize,
-) -> (usize, usize, usize, usize) {
-    let min_r = cursor_r.min(anchor_r);
-    let max_r = cursor_r.max(anchor_r);
-    let min_c = cursor_c.min(anchor_c);
-    let max_c = cursor_c.max(anchor_c);
-    (min_r, max_r, min_c, max_c)
-}
-
-/// The main UI rendering logic.
-/// Ratatui calls this to paint the `App` onto the `Frame`.
-impl Widget for &App {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        // Construct the title bar
-        let title = Line::from(format!(" Conway's Game of Rust {}", self.mode).bold());
-
-        // Dynamic help text at the bottom based on current mode
-        let instructions = {
-            match self.mode {
-                Mode::NORMAL => Line::from(vec![
-                    " Reset ".into(),
-                    "<R>".blue().bold(),
-                    " Selection Movement ".into(),
-                    "hjkl / ← ↓ ↑ →".blue().bold(),
-                    " Pause/Unpause Simulation ".into(),
-                    "<Enter>".blue().bold(),
-                    " Toggle Selected Cell(s) ".into(),
-                    "<Space>".blue().bold(),
-                    " Visual Mode ".into(),
-                    "<V>".blue().bold(),
-                    " Quit ".into(),
-                    "<Q> ".blue().bold(),
-                ]),
-                Mode::RUNNING => Line::from(vec![
-                    " Pause/Unpause Simulation ".into(),
-                    "<Enter>".blue().bold(),
-                    " Quit ".into(),
-                    "<Q> ".blue().bold(),
-                ]),
-                Mode::VISUAL => Line::from(vec![
-                    " Reset ".into(),
-                    "<R>".blue().bold(),
-                    " Selection Movement ".into(),
-                    "hjkl / ← ↓ ↑ →".blue().bold(),
-                    " Pause/Unpause Simulation ".into(),
-                    "<Enter>".blue().bold(),
-                    " Toggle Selected Cell(s) ".into(),
-                    "<Space>".blue().bold(),
-                    " Normal Mode ".into(),
-                    "<Esc>".blue().bold(),
-                    " Quit ".into(),
-                    "<Q> ".blue().bold(),
-                ]),
+            // '?' opens the key binding overlay
+            KeyCode::Char('?') => {
+                self.show_help = true;
+                self.help_scroll = 0;
             }
-        };
+            // 'H' toggles the neighbor-count heatmap (works in every mode)
+            KeyCode::Char('H') => {
+                self.heatmap = !self.heatmap;
+            }
+            // 'E' toggles coloring live cells by age
+            KeyCode::Char('E') => {
+                self.age_colors = !self.age_colors;
+            }
+            // 'F' toggles the preview of which cells change next generation
+            KeyCode::Char('F') if self.mode != Mode::RUNNING => {
+                self.preview = !self.preview;
+            }
+            // 'A' cycles the render style (aspect correction)
+            KeyCode::Char('A') => {
+                self.render_style = self.render_style.next();
+            }
+            // 'T' cycles the color theme
+            KeyCode::Char('T') => {
+                self.theme_index = (self.theme_index + 1) % THEMES.len();
+            }
+            // 'W' switches between a torus and a bounded board, even mid-run. The cells
+            // stay put; the next generation just counts neighbors the other way.
+            KeyCode::Char('W') => {
+                let mode = match self.grid.boundary_mode() {
+                    BoundaryMode::Wrap => BoundaryMode::Dead,
+                    _ => BoundaryMode::Wrap,
+                };
+                self.grid.set_boundary_mode(mode);
+            }
+            // 's' cycles the mirror symmetry used when toggling cells
+            KeyCode::Char('s') if self.mode != Mode::RUNNING => {
+                self.symmetry = self.symmetry.next();
+            }
+            // 'c' stamps a filled disk centered on the cursor, '<' / '>' adjust its radius
+            KeyCode::Char('c') if self.mode == Mode::NORMAL => {
+                self.history.record(&self.grid);
+                self.grid
+                    .draw_disk(self.cursor_pos, self.disk_radius, CellState::Alive);
+            }
+            KeyCode::Char('<') if self.mode == Mode::NORMAL => {
+                self.disk_radius = self.disk_radius.saturating_sub(1);
+            }
+            KeyCode::Char('>') if self.mode == Mode::NORMAL => {
+                self.disk_radius += 1;
+            }
+            // 'i' places the selected library pattern at the cursor, Tab picks the next one
+            KeyCode::Char('i') if self.mode == Mode::NORMAL => {
+                self.history.record(&self.grid);
+                self.grid.stamp_pattern(self.pattern, row, col);
+            }
+            KeyCode::Tab if self.mode == Mode::NORMAL => {
+                self.pattern = self.pattern.next();
+            }
+            // 'o' in Visual Mode sets only the border of the selection alive
+            KeyCode::Char('o') if self.mode == Mode::VISUAL => {
+                if let Some((anchor_r, anchor_c)) = self.selection_anchor {
+                    let (min_r, max_r, min_c, max_c) =
+                        get_row_and_col_span(row, col, anchor_r, anchor_c);
 
-        // Create the border block
-        let block = Block::bordered()
-            .title(title.centered())
-            .title_bottom(instructions.centered())
-            .border_set(border::THICK);
+                    self.history.record(&self.grid);
+                    self.grid
+                        .draw_rect_outline(min_r, max_r, min_c, max_c, CellState::Alive);
+                }
 
-        let mut grid_lines = Vec::new();
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // 'a' / 'd' in Visual Mode force every selected cell Alive / Dead
+            KeyCode::Char(key @ ('a' | 'd')) if self.mode == Mode::VISUAL => {
+                if let Some((min_r, max_r, min_c, max_c)) = self.selection_rect() {
+                    let state = if key == 'a' {
+                        CellState::Alive
+                    } else {
+                        CellState::Dead
+                    };
+                    self.history.record(&self.grid);
+                    self.grid.fill_region(min_r, max_r, min_c, max_c, state);
+                }
 
-        // --- Render the Grid ---
-        for r in 0..self.grid.height {
-            let mut row_spans = Vec::new();
-
-            for c in 0..self.grid.width {
-                // Determine the character symbol (Block for Alive, Dotted for Dead)
-                let symbol = match self.grid.get(r, c) {
-                    Some(CellState::Alive) => "██",
-                    _ => "░░",
-                };
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // '>' / '<' in Visual Mode rotate the selection a quarter turn clockwise /
+            // counterclockwise about its top-left corner, '|' / '_' mirror it in place
+            KeyCode::Char(key @ ('>' | '<' | '|' | '_')) if self.mode == Mode::VISUAL => {
+                if let Some((min_r, max_r, min_c, max_c)) = self.selection_rect() {
+                    let transform = match key {
+                        '>' => Transform::RotateCw,
+                        '<' => Transform::RotateCcw,
+                        '|' => Transform::FlipHorizontal,
+                        _ => Transform::FlipVertical,
+                    };
+                    self.history.record(&self.grid);
+                    self.grid
+                        .transform_region(transform, min_r, max_r, min_c, max_c);
+                }
 
-                // Check if the current cell falls inside the visual selection box
-                let is_in_selection = if self.mode == Mode::VISUAL {
-                    if let Some((anchor_r, anchor_c)) = self.selection_anchor {
-                        let (cursor_r, cursor_c) = self.cursor_pos;
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // 'P' in Visual Mode pins the selection as an analysis region that stays
+            // highlighted (with a live-cell count) while the simulation runs
+            KeyCode::Char('P') if self.mode == Mode::VISUAL => {
+                if let Some((anchor_r, anchor_c)) = self.selection_anchor {
+                    self.pinned_region = Some(get_row_and_col_span(row, col, anchor_r, anchor_c));
+                }
 
-                        let (min_r, max_r, min_c, max_c) =
-                            get_row_and_col_span(cursor_r, cursor_c, anchor_r, anchor_c);
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // 'y' in Visual Mode copies the selection for pasting with 'p'
+            KeyCode::Char('y') if self.mode == Mode::VISUAL => {
+                if let Some((min_r, max_r, min_c, max_c)) = self.selection_rect() {
+                    self.clipboard = Some(self.grid.copy_block(min_r, max_r, min_c, max_c));
+                }
 
-                        r >= min_r && r <= max_r && c >= min_c && c <= max_c
-                    } else {
-                        false
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // 'Y' copies the whole board to the system clipboard, for sharing
+            KeyCode::Char('Y') if matches!(self.mode, Mode::NORMAL | Mode::RUNNING) => {
+                self.status = self.run_command(Command::YankRle);
+            }
+            // 'p' pastes the last yanked region with its top-left at the cursor
+            KeyCode::Char('p') if self.mode == Mode::NORMAL => {
+                if let Some(clipboard) = &self.clipboard {
+                    self.history.record(&self.grid);
+                    self.grid.stamp_block(clipboard, row, col);
+                }
+            }
+            // 't' in Visual Mode tiles the selection with the last yanked region,
+            // toggling the cells under its live ones
+            KeyCode::Char('t') if self.mode == Mode::VISUAL => {
+                match (self.selection_rect(), &self.clipboard) {
+                    (Some((min_r, max_r, min_c, max_c)), Some(clipboard)) => {
+                        self.history.record(&self.grid);
+                        self.grid.tile_region(min_r, max_r, min_c, max_c, clipboard);
                     }
-                } else {
-                    false
-                };
+                    (Some(_), None) => self.status = "Nothing yanked to tile with".to_string(),
+                    (None, _) => {}
+                }
 
-                // Apply styling (Colors) based on state:
-                // 1. Cursor position (White bg/Gray fg)
-                // 2. Selection area (Blue theme)
-                // 3. Normal cell (White fg)
-                let style = if (r, c) == self.cursor_pos && self.mode != Mode::RUNNING {
-                    Style::default().fg(Color::DarkGray).bg(Color::White)
-                } else if is_in_selection {
-                    match self.grid.get(r, c) {
-                        Some(CellState::Alive) => {
-                            Style::default().bg(Color::White).fg(Color::LightBlue)
-                        }
-                        _ => Style::default().bg(Color::LightBlue).fg(Color::White),
-                    }
-                } else {
-                    Style::default().fg(Color::White)
-                };
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
+            }
+            // 'P' outside Visual Mode unpins the region
+            KeyCode::Char('P') => {
+                self.pinned_region = None;
+            }
+            // 'L' in Visual Mode draws a line of live cells from the anchor to the cursor
+            KeyCode::Char('L') if self.mode == Mode::VISUAL => {
+                if let Some((anchor_r, anchor_c)) = self.selection_anchor {
+                    self.history.record(&self.grid);
+                    self.grid
+                        .draw_line(anchor_r, anchor_c, row, col, CellState::Alive);
+                }
 
-                row_spans.push(Span::styled(symbol, style));
+                self.mode = Mode::NORMAL;
+                self.selection_anchor = None;
             }
+            _ => {}
+        }
+    }
 
-            grid_lines.push(Line::from(row_spans));
+    /// Edits the `:` prompt: typed characters are appended, Backspace deletes (leaving
+    /// the prompt once it's empty, like Vim), Enter runs the command and Esc cancels it.
+    fn handle_command_key(&mut self, key_event: KeyEvent) {
+        // Only a run of Tabs cycles through the same matches
+        if key_event.code != KeyCode::Tab {
+            self.completion = None;
+        }
+        match key_event.code {
+            KeyCode::Tab => self.complete_command(),
+            KeyCode::Char(ch) => self.command.push(ch),
+            KeyCode::Backspace if self.command.is_empty() => self.mode = Mode::NORMAL,
+            KeyCode::Backspace => {
+                self.command.pop();
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::NORMAL;
+                let input = std::mem::take(&mut self.command);
+                self.status = match Command::parse(&input) {
+                    Ok(Some(command)) => self.run_command(command),
+                    Ok(None) => String::new(),
+                    Err(message) => message,
+                };
+            }
+            KeyCode::Esc => {
+                self.mode = Mode::NORMAL;
+                self.command.clear();
+            }
+            _ => {}
         }
+    }
 
-        let grid_text = Text::from(grid_lines);
+    /// Completes the last word at the `:` prompt: a command name, or an argument such
+    /// as a file path or pattern name. Pressing Tab again swaps in the next match.
+    fn complete_command(&mut self) {
+        if let Some(completion) = &mut self.completion {
+            completion.index = (completion.index + 1) % completion.matches.len();
+        } else {
+            let (start, matches) = completions(&self.command, self.slot_dir.as_deref());
+            if matches.is_empty() {
+                return;
+            }
+            self.completion = Some(Completion {
+                start,
+                matches,
+                index: 0,
+            });
+        }
+        let completion = self.completion.as_ref().expect("set above");
+        self.command.truncate(completion.start);
+        self.command.push_str(&completion.matches[completion.index]);
+    }
 
-        // Render the text inside the block
-        Paragraph::new(grid_text)
-            .centered()
-            .block(block)
-            .render(area, buf);
+    /// Carries out a parsed command, returning the message for the status line.
+    /// Failures are reported there rather than ending the session.
+    fn run_command(&mut self, command: Command) -> String {
+        match command {
+            Command::Write(path) => {
+                let text = match FileFormat::of(&path) {
+                    FileFormat::Rle => self.grid.to_rle(),
+                    FileFormat::Plaintext => self.grid.to_plaintext(),
+                    FileFormat::Board => self.grid.to_string(),
+                };
+                match std::fs::write(&path, text) {
+                    Ok(()) => format!("Wrote {path}"),
+                    Err(err) => format!("Can't write {path}: {err}"),
+                }
+            }
+            Command::Load(path) => self.open(&path, &path),
+            Command::Save(name) => {
+                let path = match self.slot_path(&name) {
+                    Ok(path) => path,
+                    Err(err) => return err,
+                };
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .and_then(|()| std::fs::write(&path, self.grid.to_rle()));
+                match written {
+                    Ok(()) => format!("Saved slot {name}"),
+                    Err(err) => format!("Can't save slot {name}: {err}"),
+                }
+            }
+            Command::LoadSlot(name) => match self.slot_path(&name) {
+                Ok(path) => self.open(&path.to_string_lossy(), &format!("slot {name}")),
+                Err(err) => err,
+            },
+            Command::SlotMenu => {
+                let Some(dir) = &self.slot_dir else {
+                    return "No directory for saved slots: set $XDG_DATA_HOME or $HOME".to_string();
+                };
+                match list_slots(dir) {
+                    Ok(slots) if slots.is_empty() => {
+                        format!("No saved slots in {}", dir.display())
+                    }
+                    Ok(slots) => {
+                        self.slot_menu = Some(SlotMenu { slots, selected: 0 });
+                        String::new()
+                    }
+                    Err(err) => format!("Can't list {}: {err}", dir.display()),
+                }
+            }
+            Command::Set(Setting::Fit(fit)) => {
+                self.load_fit = fit;
+                format!("fit = {fit}")
+            }
+            Command::Set(Setting::Expand(expand)) => {
+                self.auto_expand = expand;
+                format!("expand = {}", if expand { "on" } else { "off" })
+            }
+            Command::Set(Setting::Loop(pause)) => {
+                self.pause_on_loop = pause;
+                format!("loop = {}", if pause { "on" } else { "off" })
+            }
+            Command::Set(Setting::History(limit)) => {
+                self.history.set_limit(limit);
+                trim_front(&mut self.past_generations, limit);
+                format!(
+                    "history = {limit} ({} in use)",
+                    format_bytes(self.history_bytes())
+                )
+            }
+            Command::Run(generations) => {
+                self.history.record(&self.grid);
+                // The generations in between aren't kept, so there's nothing to step back to
+                self.past_generations.clear();
+                self.fast_forward = Some(FastForward {
+                    done: 0,
+                    total: generations,
+                    watched: None,
+                });
+                String::new()
+            }
+            Command::RunFor(generations) => {
+                // Recorded like any other run, so it can be undone at once
+                self.history.record(&self.grid);
+                self.mode = Mode::RUNNING;
+                self.run_for = Some((generations, generations));
+                String::new()
+            }
+            Command::Rule(Some(rule)) => {
+                self.grid.set_rule(rule);
+                format!("rule = {}", rule_label(&rule))
+            }
+            Command::Rule(None) => {
+                let rule = LifelikeRule::from_rule(self.grid.rule());
+                format!("rule = {} (presets: {})", rule_label(&rule), preset_names())
+            }
+            Command::Speed(ms) => {
+                self.tick_ms = ms.clamp(MIN_TICK_MS, MAX_TICK_MS);
+                format!("Speed set to {}ms/gen", self.tick_ms)
+            }
+            Command::Resize(width, height) => {
+                let previous = self.grid.snapshot();
+                match self.grid.resize(width, height) {
+                    Ok(()) => {
+                        self.history.record_snapshot(previous);
+                        self.clamp_cursor();
+                        self.past_generations.clear();
+                        format!("Resized to {width}x{height}")
+                    }
+                    Err(err) => format!("Can't resize: {err}"),
+                }
+            }
+            Command::Place(pattern) => {
+                let (row, col) = self.cursor_pos;
+                self.history.record(&self.grid);
+                self.grid.stamp_pattern(pattern, row, col);
+                self.pattern = pattern;
+                String::new()
+            }
+            Command::Shift(rows, cols) => {
+                self.history.record(&self.grid);
+                self.grid.translate(rows, cols);
+                String::new()
+            }
+            Command::Transform(transform) => {
+                self.history.record(&self.grid);
+                self.grid.transform(transform);
+                self.clamp_cursor();
+                self.past_generations.clear();
+                String::new()
+            }
+            Command::Goto(row, col) => {
+                // The view follows the cursor on the next frame
+                self.cursor_pos = (row.min(self.grid.height - 1), col.min(self.grid.width - 1));
+                if self.cursor_pos == (row, col) {
+                    String::new()
+                } else {
+                    let (row, col) = self.cursor_pos;
+                    format!("Off the board, moved to row {row} col {col}")
+                }
+            }
+            Command::Universe => format!("universe = {}", self.grid.universe()),
+            Command::Classify => format!("Board: {}", self.grid.classify()),
+            Command::Watch(watch) => {
+                self.watch = Some(watch);
+                format!("Watching for {watch}")
+            }
+            Command::Unwatch => {
+                self.watch = None;
+                "Stopped watching".to_string()
+            }
+            Command::Backend(Some(backend)) => {
+                self.auto_backend = false;
+                self.grid.set_backend(backend);
+                self.backend_label()
+            }
+            Command::Backend(None) => {
+                self.auto_backend = true;
+                self.pick_backend();
+                self.backend_label()
+            }
+            Command::ShowBackend => self.backend_label(),
+            Command::ShowWatch => match self.watch {
+                Some(watch) => format!("Watching for {watch}"),
+                None => "Not watching anything".to_string(),
+            },
+            Command::YankRle => {
+                let rle = self.grid.to_rle();
+                match (self.system_clipboard)(&rle) {
+                    // Whether the terminal acted on it can't be known, so don't claim a copy
+                    Ok(()) => format!(
+                        "Sent the board as RLE ({} bytes) to the terminal's clipboard (needs OSC 52)",
+                        rle.len()
+                    ),
+                    Err(err) => format!("Can't copy to the clipboard: {err}"),
+                }
+            }
+            Command::Clear => {
+                self.history.record(&self.grid);
+                self.grid.reset();
+                String::new()
+            }
+            Command::Quit => {
+                self.exit();
+                String::new()
+            }
+        }
+    }
+
+    /// Replaces the board with the pattern or board at `path`, called `label` in the
+    /// message returned for the status line. The boundary mode and neighborhood carry
+    /// over, as does the rule unless an RLE pattern names its own.
+    fn open(&mut self, path: &str, label: &str) -> String {
+        match self.load_file(path) {
+            Ok((mut grid, note)) => {
+                let mut universe = self.grid.universe().clone();
+                if let FileFormat::Rle = FileFormat::of(path) {
+                    universe.rule = grid.universe().rule.clone();
+                }
+                grid.set_universe(universe);
+                self.history.record(&self.grid);
+                self.grid = grid;
+                self.cursor_pos = (0, 0);
+                self.clamp_cursor();
+                // Stepping back shouldn't cross into the board that was replaced
+                self.past_generations.clear();
+                match note {
+                    Some(note) => format!("Loaded {label}: {note}"),
+                    None => format!("Loaded {label}"),
+                }
+            }
+            Err(err) => format!("Can't load {label}: {err}"),
+        }
+    }
+
+    /// The file a named slot is kept in. Names are plain file names, so a slot can't
+    /// be written outside the slot directory.
+    fn slot_path(&self, name: &str) -> Result<PathBuf, String> {
+        if name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!("Invalid slot name: {name}"));
+        }
+        match &self.slot_dir {
+            Some(dir) => Ok(dir.join(format!("{name}.rle"))),
+            None => Err("No directory for saved slots: set $XDG_DATA_HOME or $HOME".to_string()),
+        }
+    }
+
+    /// Reads `path` in the format given by its extension. Patterns (RLE and plaintext)
+    /// are centered on a board the size of the current one, which is grown or the
+    /// pattern clipped if it doesn't fit, according to `load_fit`. A saved board
+    /// replaces it outright, dimensions included.
+    ///
+    /// Returns the new grid and, if the pattern didn't fit, a note saying what was done.
+    fn load_file(&self, path: &str) -> Result<(Grid, Option<String>), String> {
+        let loaded = read_board_file(path)?;
+        match FileFormat::of(path) {
+            FileFormat::Rle | FileFormat::Plaintext => {
+                fit_pattern(&loaded, self.grid.width, self.grid.height, self.load_fit)
+            }
+            FileFormat::Board => Ok((loaded, None)),
+        }
+    }
+
+    /// Pulls the cursor and selection anchor back inside the grid and drops a pinned
+    /// region that no longer fits, after something (a resize, a load, or undoing
+    /// either) changed the grid's dimensions. Call it after every grid replacement.
+    fn clamp_cursor(&mut self) {
+        let (max_row, max_col) = (
+            self.grid.height.saturating_sub(1),
+            self.grid.width.saturating_sub(1),
+        );
+        let (row, col) = self.cursor_pos;
+        self.cursor_pos = (row.min(max_row), col.min(max_col));
+        self.selection_anchor = self
+            .selection_anchor
+            .map(|(row, col)| (row.min(max_row), col.min(max_col)));
+        if self.pinned_region.is_some_and(|(_, max_r, _, max_c)| {
+            max_r >= self.grid.height || max_c >= self.grid.width
+        }) {
+            self.pinned_region = None;
+        }
+    }
+
+    /// Toggles (row, col) along with its mirror images under the current symmetry.
+    /// Coordinates on a center row/column mirror onto themselves, so duplicates are
+    /// dropped to avoid toggling the same cell twice.
+    fn toggle_with_symmetry(&mut self, row: usize, col: usize) {
+        let mirror_r = self.grid.height - 1 - row;
+        let mirror_c = self.grid.width - 1 - col;
+
+        let mut targets = vec![(row, col)];
+        match self.symmetry {
+            Symmetry::Off => {}
+            Symmetry::Vertical => targets.push((row, mirror_c)),
+            Symmetry::Horizontal => targets.push((mirror_r, col)),
+            Symmetry::Both => {
+                targets.push((row, mirror_c));
+                targets.push((mirror_r, col));
+                targets.push((mirror_r, mirror_c));
+            }
+        }
+        targets.sort_unstable();
+        targets.dedup();
+
+        for (r, c) in targets {
+            self.grid.toggle_cell(r, c);
+        }
+    }
+
+    /// Feeds a key into the pending-sequence state machine. Returns true if the key was
+    /// consumed, either by extending a sequence, completing one (which runs its action),
+    /// or by breaking one that was in progress (which discards it, as Vim does).
+    fn handle_key_sequence(&mut self, key_event: KeyEvent) -> bool {
+        let was_pending = !self.pending_keys.is_empty();
+        let ch = match key_event.code {
+            KeyCode::Char(ch)
+                if self.mode != Mode::RUNNING
+                    && !key_event
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                ch
+            }
+            // Esc and any other key cancel a pending sequence and are handled normally
+            _ => {
+                self.clear_pending_keys();
+                return false;
+            }
+        };
+
+        let mut candidate = self.pending_keys.clone();
+        candidate.push(ch);
+
+        let mut sequences = KEY_SEQUENCES
+            .iter()
+            .filter(|(_, _, modes)| modes.contains(&self.mode));
+        if let Some(&(_, action, _)) = sequences.clone().find(|(seq, _, _)| *seq == candidate) {
+            self.clear_pending_keys();
+            self.run_sequence_action(action);
+            return true;
+        }
+        if sequences.any(|(seq, _, _)| seq.starts_with(&candidate)) {
+            self.pending_keys = candidate;
+            self.pending_since = Some(Instant::now());
+            return true;
+        }
+
+        self.clear_pending_keys();
+        was_pending
+    }
+
+    /// Runs the action bound to a completed key sequence.
+    fn run_sequence_action(&mut self, action: SequenceAction) {
+        match action {
+            SequenceAction::JumpToTop => self.cursor_pos.0 = 0,
+            SequenceAction::CenterView => self.center_on_cursor(grid_inner_area(self.last_area)),
+            SequenceAction::ClearRow => {
+                self.history.record(&self.grid);
+                self.grid.clear_row(self.cursor_pos.0);
+            }
+            SequenceAction::ClearCol => {
+                self.history.record(&self.grid);
+                self.grid.clear_col(self.cursor_pos.1);
+            }
+        }
+    }
+
+    fn clear_pending_keys(&mut self) {
+        self.pending_keys.clear();
+        self.pending_since = None;
+    }
+
+    /// Drops a pending key sequence that has waited longer than `KEY_SEQUENCE_TIMEOUT`.
+    fn expire_pending_keys(&mut self) {
+        if self
+            .pending_since
+            .is_some_and(|since| since.elapsed() >= KEY_SEQUENCE_TIMEOUT)
+        {
+            self.clear_pending_keys();
+        }
+    }
+
+    /// The count and key sequence typed so far, like Vim's `showcmd`.
+    fn pending_label(&self) -> String {
+        match self.pending_count {
+            Some(count) => format!("{count}{}", self.pending_keys),
+            None => self.pending_keys.clone(),
+        }
+    }
+
+    /// What `:backend` reports, e.g. "backend = sparse (auto)".
+    fn backend_label(&self) -> String {
+        let auto = if self.auto_backend { " (auto)" } else { "" };
+        format!("backend = {}{auto}", self.grid.backend())
+    }
+
+    /// Label for the current speed: the preset number if it matches one, otherwise
+    /// the raw interval.
+    fn speed_label(&self) -> String {
+        match SPEED_PRESETS.iter().position(|&ms| ms == self.tick_ms) {
+            Some(preset) => format!("[SPEED {}]", preset + 1),
+            None => format!("[{}ms]", self.tick_ms),
+        }
+    }
+
+    fn exit(&mut self) {
+        self.exit = true;
+    }
+}
+
+/// A command entered at the `:` prompt.
+enum Command {
+    Write(String),        // `:w <path>` saves the board
+    Load(String),         // `:e <path>` replaces the board from a file
+    Save(String),         // `:save <name>` writes the board to a named slot
+    LoadSlot(String),     // `:load <name>` replaces the board from a named slot
+    SlotMenu,             // `:load` opens a menu of the saved slots
+    Speed(u64),           // `:speed <ms>` sets the interval between generations
+    RunFor(usize),        // `:runfor <n>` runs at the current speed, pausing after n generations
+    Run(u64),             // `:run <n>` computes n generations without drawing them
+    Resize(usize, usize), // `:resize <width> <height>` changes the board size
+    Place(NamedPattern),  // `:place <pattern>` stamps a library pattern at the cursor
+    Shift(isize, isize),  // `:shift <rows> <cols>` moves every live cell by an offset
+    Transform(Transform), // `:rotate [cw|ccw]` / `:flip <h|v>` turns or mirrors the board
+    Set(Setting),         // `:set <option> <value>` changes a setting
+    Goto(usize, usize),   // `:goto <row> <col>` moves the cursor, clamped to the board
+    YankRle,              // `:yank rle` copies the board to the system clipboard as RLE
+    Universe,             // `:universe` shows the rule, boundary mode and neighborhood
+    Classify,             // `:classify` says what the board has settled into
+    Watch(Watch),         // `:watch pop >|< <n> [pause]` reports the gen it first passes n
+    Unwatch,              // `:watch off` stops watching
+    Clear,                // `:clear` kills every cell
+    Quit,                 // `:q` exits, same as `<Q>`
+    // `:rule [preset|rulestring]` sets the rule, or shows it with no argument
+    Rule(Option<LifelikeRule>),
+    // `:watch` shows the population threshold being watched for
+    ShowWatch,
+    // `:backend` shows how the cells are kept
+    ShowBackend,
+    // `:backend <auto|dense|sparse>` picks how cells are kept, None for auto
+    Backend(Option<Backend>),
+}
+
+impl Command {
+    /// Parses the text typed after `:`. A blank line is `Ok(None)`, and anything
+    /// malformed is an error message meant for the status line.
+    fn parse(input: &str) -> Result<Option<Command>, String> {
+        let mut words = input.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(None);
+        };
+        let args: Vec<&str> = words.collect();
+        let number = |arg: &str| {
+            arg.parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("Not a positive number: {arg}"))
+        };
+
+        let command = match (name, args.as_slice()) {
+            ("w" | "write", [path]) => Command::Write(path.to_string()),
+            ("e" | "edit", [path]) => Command::Load(path.to_string()),
+            ("save", [name]) => Command::Save(name.to_string()),
+            ("load", [name]) => Command::LoadSlot(name.to_string()),
+            ("load", []) => Command::SlotMenu,
+            ("speed", [ms]) => Command::Speed(number(ms)? as u64),
+            ("runfor", [generations]) => Command::RunFor(number(generations)?),
+            ("run", [generations]) => Command::Run(number(generations)? as u64),
+            ("resize", [width, height]) => Command::Resize(number(width)?, number(height)?),
+            ("place", [name]) => {
+                let pattern = name
+                    .parse::<NamedPattern>()
+                    .map_err(|err| err.to_string())?;
+                Command::Place(pattern)
+            }
+            ("shift", [rows, cols]) => {
+                let offset = |arg: &str| {
+                    arg.parse::<isize>()
+                        .map_err(|_| format!("Not a number: {arg}"))
+                };
+                Command::Shift(offset(rows)?, offset(cols)?)
+            }
+            ("rotate", [] | ["cw"]) => Command::Transform(Transform::RotateCw),
+            ("rotate", ["ccw"]) => Command::Transform(Transform::RotateCcw),
+            ("flip", ["h"]) => Command::Transform(Transform::FlipHorizontal),
+            ("flip", ["v"]) => Command::Transform(Transform::FlipVertical),
+            ("set", args) => Command::Set(Setting::parse(args)?),
+            ("rule", []) => Command::Rule(None),
+            ("rule", [rule]) => {
+                let rule = match rule.parse::<RulePreset>() {
+                    Ok(preset) => preset.rule(),
+                    Err(_) => rule.parse().map_err(|_| {
+                        format!(
+                            "Not a preset or rulestring: {rule} (try {})",
+                            preset_names()
+                        )
+                    })?,
+                };
+                Command::Rule(Some(rule))
+            }
+            ("goto", [row, col]) => {
+                let coordinate = |arg: &str| {
+                    arg.parse::<usize>()
+                        .map_err(|_| format!("Not a row or column: {arg}"))
+                };
+                Command::Goto(coordinate(row)?, coordinate(col)?)
+            }
+            ("yank", ["rle"]) => Command::YankRle,
+            ("universe", []) => Command::Universe,
+            ("classify", []) => Command::Classify,
+            ("watch", []) => Command::ShowWatch,
+            ("backend", []) => Command::ShowBackend,
+            ("backend", ["auto"]) => Command::Backend(None),
+            ("backend", ["dense"]) => Command::Backend(Some(Backend::Dense)),
+            ("backend", ["sparse"]) => Command::Backend(Some(Backend::Sparse)),
+            ("watch", ["off"]) => Command::Unwatch,
+            ("watch", ["pop", comparison, threshold, rest @ ..]) if rest.len() <= 1 => {
+                let comparison = match *comparison {
+                    ">" => Comparison::Above,
+                    "<" => Comparison::Below,
+                    _ => return Err(format!("Not > or <: {comparison}")),
+                };
+                let threshold = threshold
+                    .parse()
+                    .map_err(|_| format!("Not a population: {threshold}"))?;
+                let pause = match rest {
+                    [] => false,
+                    ["pause"] => true,
+                    [word] => return Err(format!("Expected `pause`, not {word}")),
+                    _ => unreachable!(),
+                };
+                Command::Watch(Watch {
+                    comparison,
+                    threshold,
+                    pause,
+                })
+            }
+            ("clear", []) => Command::Clear,
+            ("q" | "quit", []) => Command::Quit,
+            ("w" | "write" | "e" | "edit", _) => return Err(format!("Usage: :{name} <path>")),
+            ("save", _) => return Err("Usage: :save <name>".to_string()),
+            ("load", _) => return Err("Usage: :load [name]".to_string()),
+            ("speed", _) => return Err("Usage: :speed <ms>".to_string()),
+            ("runfor", _) => return Err("Usage: :runfor <generations>".to_string()),
+            ("run", _) => return Err("Usage: :run <generations>".to_string()),
+            ("resize", _) => return Err("Usage: :resize <width> <height>".to_string()),
+            ("place", _) => return Err("Usage: :place <pattern>".to_string()),
+            ("rule", _) => return Err("Usage: :rule [preset|rulestring]".to_string()),
+            ("shift", _) => return Err("Usage: :shift <rows> <cols>".to_string()),
+            ("goto", _) => return Err("Usage: :goto <row> <col>".to_string()),
+            ("yank", _) => return Err("Usage: :yank rle".to_string()),
+            ("backend", _) => return Err("Usage: :backend [auto|dense|sparse]".to_string()),
+            ("watch", _) => return Err("Usage: :watch pop >|< <n> [pause], or off".to_string()),
+            ("rotate", _) => return Err("Usage: :rotate [cw|ccw]".to_string()),
+            ("flip", _) => return Err("Usage: :flip <h|v>".to_string()),
+            ("clear" | "q" | "quit" | "universe" | "classify", _) => {
+                return Err(format!(":{name} takes no arguments"))
+            }
+            _ => return Err(format!("Unknown command: {name}")),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Every command's full name, offered by Tab at the start of the `:` prompt.
+const COMMAND_NAMES: [&str; 22] = [
+    "backend", "classify", "clear", "edit", "flip", "goto", "load", "place", "quit", "resize",
+    "rotate", "rule", "run", "runfor", "save", "set", "shift", "speed", "universe", "watch",
+    "write", "yank",
+];
+
+/// Matches for a Tab at the `:` prompt, kept so repeated presses can cycle through them.
+struct Completion {
+    start: usize,         // Byte offset in the prompt where the completed word begins
+    matches: Vec<String>, // Replacements for that word, in the order they're offered
+    index: usize,         // The match currently filled in
+}
+
+/// What the last word of `input` could be completed to, given the words before it,
+/// along with the byte offset that word starts at. Command names come with a trailing
+/// space, ready for the argument.
+fn completions(input: &str, slot_dir: Option<&Path>) -> (usize, Vec<String>) {
+    let start = input.rfind(' ').map_or(0, |space| space + 1);
+    let word = &input[start..];
+    let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+    let candidates: Vec<String> = match input[..start].split_whitespace().collect::<Vec<_>>()[..] {
+        [] => COMMAND_NAMES
+            .iter()
+            .map(|name| format!("{name} "))
+            .collect(),
+        ["w" | "write" | "e" | "edit"] => return (start, path_completions(word)),
+        ["load"] => slot_dir
+            .and_then(|dir| list_slots(dir).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|slot| slot.name)
+            .collect(),
+        ["place"] => names(&NamedPattern::ALL.map(NamedPattern::name)),
+        ["rule"] => names(&RulePreset::ALL.map(RulePreset::name)),
+        ["rotate"] => names(&["cw", "ccw"]),
+        ["flip"] => names(&["h", "v"]),
+        ["yank"] => names(&["rle"]),
+        ["watch"] => names(&["pop", "off"]),
+        ["backend"] => names(&["auto", "dense", "sparse"]),
+        ["set"] => names(&["expand ", "fit ", "history ", "loop "]),
+        ["set", "fit"] => names(&["resize", "clip"]),
+        ["set", "expand" | "loop"] => names(&["on", "off"]),
+        _ => Vec::new(),
+    };
+    let matches = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+    (start, matches)
+}
+
+/// Files and directories that `word` could be the start of, sorted, with a `/` after
+/// directories. Hidden entries are only offered once a `.` has been typed.
+fn path_completions(word: &str) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(slash) => (&word[..=slash], &word[slash + 1..]),
+        None => ("", word),
+    };
+    let Ok(entries) = std::fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.file_type().ok()?.is_dir() {
+                "/"
+            } else {
+                ""
+            };
+            Some(format!("{dir}{name}{slash}"))
+        })
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// A setting changed with `:set`.
+enum Setting {
+    Fit(LoadFit),   // `:set fit <resize|clip>`: what loading does with an oversized pattern
+    History(usize), // `:set history <n>`: snapshots kept for undo and for stepping back
+    Loop(bool),     // `:set loop <on|off>`: whether a run pauses once the board starts repeating
+    Expand(bool),   // `:set expand <on|off>`: whether the board grows when life nears an edge
+}
+
+impl Setting {
+    /// Parses the words after `:set`.
+    fn parse(args: &[&str]) -> Result<Setting, String> {
+        match args {
+            ["fit", "resize"] => Ok(Setting::Fit(LoadFit::Resize)),
+            ["fit", "clip"] => Ok(Setting::Fit(LoadFit::Clip)),
+            ["fit", ..] => Err("Usage: :set fit <resize|clip>".to_string()),
+            ["history", limit] => limit
+                .parse()
+                .map(Setting::History)
+                .map_err(|_| format!("Invalid snapshot count: {limit}")),
+            ["history", ..] => Err("Usage: :set history <snapshots>".to_string()),
+            ["loop", "on"] => Ok(Setting::Loop(true)),
+            ["loop", "off"] => Ok(Setting::Loop(false)),
+            ["loop", ..] => Err("Usage: :set loop <on|off>".to_string()),
+            ["expand", "on"] => Ok(Setting::Expand(true)),
+            ["expand", "off"] => Ok(Setting::Expand(false)),
+            ["expand", ..] => Err("Usage: :set expand <on|off>".to_string()),
+            [name, ..] => Err(format!("Unknown setting: {name}")),
+            [] => Err("Usage: :set <option> <value>".to_string()),
+        }
+    }
+}
+
+/// A rule's rulestring, followed by its preset name if it has one.
+fn rule_label(rule: &LifelikeRule) -> String {
+    match RulePreset::ALL
+        .into_iter()
+        .find(|preset| preset.rule() == *rule)
+    {
+        Some(preset) => format!("{rule} ({preset})"),
+        None => rule.to_string(),
+    }
+}
+
+/// The rule presets `:rule` accepts, for its messages.
+fn preset_names() -> String {
+    RulePreset::ALL.map(RulePreset::name).join(", ")
+}
+
+/// File formats for `:w` and `:e`, picked by extension.
+enum FileFormat {
+    Rle,       // `.rle`: run-length encoded pattern, as used by Golly
+    Plaintext, // `.cells`: LifeWiki plaintext pattern
+    Board,     // Anything else: the whole board as `#` / `.` rows, size included
+}
+
+impl FileFormat {
+    fn of(path: &str) -> FileFormat {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("rle") => FileFormat::Rle,
+            Some("cells") => FileFormat::Plaintext,
+            _ => FileFormat::Board,
+        }
+    }
+}
+
+/// Writes the board to `path` for `--restore`, as a whole board so its size comes
+/// back too.
+fn save_session(path: &Path, grid: &Grid) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, grid.to_string())
+}
+
+/// Puts `text` on the system clipboard with an OSC 52 escape sequence, which the
+/// terminal passes on (as do tmux and most SSH setups), so it works without a display
+/// server. Terminals that don't support it ignore the sequence, which can't be
+/// detected; not being attached to a terminal at all is reported as an error.
+fn copy_to_system_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return Err(io::Error::other("not running in a terminal"));
+    }
+    write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+    stdout.flush()
+}
+
+/// Standard base64 with padding, as OSC 52 expects.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        // A chunk of n bytes fills n + 1 characters; the rest are padding
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Where named slots are kept: `$XDG_DATA_HOME/conway`, or `~/.local/share/conway`
+/// when that isn't set.
+fn default_slot_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+    Some(data_home.join("conway"))
+}
+
+/// The `.rle` slots saved in `dir`, sorted by name. A directory that doesn't exist yet
+/// just has no slots, and files that don't parse are left out.
+fn list_slots(dir: &Path) -> Result<Vec<Slot>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.to_string()),
+    };
+    let mut slots: Vec<Slot> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "rle" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            let grid = Grid::from_rle(&std::fs::read_to_string(&path).ok()?).ok()?;
+            Some(Slot {
+                name,
+                width: grid.width,
+                height: grid.height,
+            })
+        })
+        .collect();
+    slots.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(slots)
+}
+
+/// Reads a pattern or saved board from `path`, in the format given by its extension,
+/// sized to fit exactly what's in the file.
+fn read_board_file(path: &str) -> Result<Grid, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let grid = match FileFormat::of(path) {
+        FileFormat::Rle => Grid::from_rle(&text).map_err(|err| err.to_string())?,
+        FileFormat::Plaintext => Grid::from_plaintext(&text).map_err(|err| err.to_string())?,
+        FileFormat::Board => text.parse::<Grid>().map_err(|err| err.to_string())?,
+    };
+    // The cursor needs at least one cell to sit on
+    if grid.width == 0 || grid.height == 0 {
+        return Err("the board is empty".to_string());
+    }
+    Ok(grid)
+}
+
+/// Centers `pattern` on a `width` x `height` board. If it doesn't fit, the board
+/// grows to fit it or the pattern is clipped, depending on `fit`, and a note saying
+/// which is returned along with the board.
+fn fit_pattern(
+    pattern: &Grid,
+    width: usize,
+    height: usize,
+    fit: LoadFit,
+) -> Result<(Grid, Option<String>), String> {
+    if pattern.width <= width && pattern.height <= height {
+        return Ok((centered(pattern, width, height)?, None));
+    }
+    match fit {
+        LoadFit::Resize => {
+            let (width, height) = (width.max(pattern.width), height.max(pattern.height));
+            let note = format!("board grown to {width}x{height} to fit");
+            Ok((centered(pattern, width, height)?, Some(note)))
+        }
+        LoadFit::Clip => {
+            let grid = centered(pattern, width, height)?;
+            let dropped = pattern.population() - grid.population();
+            let note = format!(
+                "WARNING: {}x{} pattern clipped to the {width}x{height} board, {dropped} live cells lost",
+                pattern.width, pattern.height
+            );
+            Ok((grid, Some(note)))
+        }
+    }
+}
+
+/// A new `width` x `height` board with the live cells of `pattern` in the middle,
+/// running the pattern's rule. Parts of a pattern larger than the board are clipped
+/// evenly from both sides.
+fn centered(pattern: &Grid, width: usize, height: usize) -> Result<Grid, String> {
+    let mut grid = Grid::try_new(width, height).map_err(|err| err.to_string())?;
+    grid.set_rule(LifelikeRule::from_rule(pattern.rule()));
+    // Negative when the pattern is the larger of the two, which crops it instead
+    let row_offset = (height as isize - pattern.height as isize) / 2;
+    let col_offset = (width as isize - pattern.width as isize) / 2;
+    let cells: Vec<(usize, usize)> = pattern
+        .live_cells()
+        .filter_map(|(r, c)| {
+            let r = r.checked_add_signed(row_offset).filter(|&r| r < height)?;
+            let c = c.checked_add_signed(col_offset).filter(|&c| c < width)?;
+            Some((r, c))
+        })
+        .collect();
+    grid.set_alive(&cells);
+    Ok(grid)
+}
+
+/// Helper function to calculate the bounding box of a selection.
+/// Takes two corners (cursor and anchor) and returns (min_row, max_row, min_col, max_col).
+fn get_row_and_col_span(
+    cursor_r: usize,
+    cursor_c: usize,
+    anchor_r: usize,
+    anchor_c: usize,
+) -> (usize, usize, usize, usize) {
+    let min_r = cursor_r.min(anchor_r);
+    let max_r = cursor_r.max(anchor_r);
+    let min_c = cursor_c.min(anchor_c);
+    let max_c = cursor_c.max(anchor_c);
+    (min_r, max_r, min_c, max_c)
+}
+
+/// Drops the oldest snapshots from the front of `buffer` until at most `limit` are left.
+fn trim_front(buffer: &mut VecDeque<GridSnapshot>, limit: usize) {
+    let excess = buffer.len().saturating_sub(limit);
+    buffer.drain(..excess);
+}
+
+/// A byte count for display, in the largest unit that keeps it at 1 or more.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Splits the terminal into the bordered board and the status line below it. On
+/// terminals too short to spare the line, the status line is dropped first.
+fn split_status_line(area: Rect) -> (Rect, Option<Rect>) {
+    if area.height <= MIN_TERMINAL_HEIGHT {
+        return (area, None);
+    }
+    let [board, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+    (board, Some(status))
+}
+
+/// The area inside the bordered block that the grid is drawn in.
+fn grid_inner_area(area: Rect) -> Rect {
+    let (board, _) = split_status_line(area);
+    Rect::new(
+        board.x + 1,
+        board.y + 1,
+        board.width.saturating_sub(2),
+        board.height.saturating_sub(2),
+    )
+}
+
+/// A progress bar for `:run`, like "[#####.....] gen 430000 / 1000000".
+fn progress_bar(done: u64, total: u64) -> String {
+    const WIDTH: u64 = 20;
+    let filled = (done * WIDTH / total.max(1)) as usize;
+    format!(
+        "[{}{}] gen {done} / {total}",
+        "#".repeat(filled),
+        ".".repeat(WIDTH as usize - filled)
+    )
+}
+
+/// A bordered popup for `width` x `height` of text, centered in `area` and shrunk to
+/// fit small terminals.
+fn popup_area(area: Rect, width: usize, height: usize) -> Rect {
+    // Border on each side, plus a column of padding either side of the text
+    let width = (width as u16 + 4).min(area.width);
+    let height = (height as u16 + 2).min(area.height);
+
+    let [popup] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [popup] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(popup);
+    popup
+}
+
+/// New scroll offset along one axis: moved the minimum needed to show `cursor` in a
+/// view `view` cells long, and kept within a grid `len` cells long.
+fn scroll_axis(offset: usize, cursor: usize, view: usize, len: usize) -> usize {
+    let offset = if cursor < offset {
+        cursor
+    } else if cursor >= offset + view {
+        cursor + 1 - view
+    } else {
+        offset
+    };
+    offset.min(len.saturating_sub(view))
+}
+
+/// Scroll offset along one axis that puts `cursor` in the middle of a view `view`
+/// cells long, kept within a grid `len` cells long. The current offset doesn't matter.
+fn center_axis(_offset: usize, cursor: usize, view: usize, len: usize) -> usize {
+    cursor
+        .saturating_sub(view / 2)
+        .min(len.saturating_sub(view))
+}
+
+/// A seed that differs on every call, so each randomize gives a new board.
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Draws a centered "terminal too small" message with the required dimensions.
+fn render_too_small(area: Rect, buf: &mut Buffer) {
+    let message = Text::from(vec![
+        Line::from("Terminal too small".bold()),
+        Line::from(format!(
+            "Need {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}, have {}x{}",
+            area.width, area.height
+        )),
+    ]);
+
+    let [message_area] = Layout::vertical([Constraint::Length(message.height() as u16)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    Paragraph::new(message).centered().render(message_area, buf);
+}
+
+// --- Render helpers ---
+impl App {
+    /// The bindings that apply in the current mode, in display order.
+    fn active_bindings(&self) -> impl Iterator<Item = &'static Binding> + '_ {
+        BINDINGS.iter().filter(|b| b.modes.contains(&self.mode))
+    }
+
+    /// A binding's action text with any placeholders filled in from the current state.
+    fn binding_action(&self, binding: &Binding) -> String {
+        binding
+            .action
+            .replace("{radius}", &self.disk_radius.to_string())
+            .replace("{tick_ms}", &self.tick_ms.to_string())
+            .replace("{pattern}", self.pattern.name())
+    }
+
+    /// The bottom-bar instructions for the current mode, generated from `BINDINGS`.
+    fn instructions_line(&self) -> Line<'static> {
+        let mut spans: Vec<Span> = Vec::new();
+        for binding in self.active_bindings() {
+            spans.push(format!(" {} ", self.binding_action(binding)).into());
+            spans.push(binding.keys.blue().bold());
+        }
+        spans.push(" ".into());
+        Line::from(spans)
+    }
+
+    /// The help overlay text: every binding, grouped under each mode it works in.
+    fn help_text(&self) -> Text<'static> {
+        let key_width = BINDINGS
+            .iter()
+            .map(|b| b.keys.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = Vec::new();
+        for mode in &HELP_MODES {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(mode.to_string().bold()));
+            for binding in BINDINGS.iter().filter(|b| b.modes.contains(mode)) {
+                lines.push(Line::from(vec![
+                    format!("  {:<key_width$}  ", binding.keys).blue().bold(),
+                    self.binding_action(binding).into(),
+                ]));
+            }
+        }
+        Text::from(lines)
+    }
+
+    /// Where the help overlay goes in `area`: centered, and shrunk to fit small terminals.
+    fn help_area(&self, area: Rect, text: &Text) -> Rect {
+        popup_area(area, text.width(), text.height())
+    }
+
+    /// How far the help overlay can scroll before its last line reaches the bottom.
+    fn max_help_scroll(&self) -> u16 {
+        let text = self.help_text();
+        let visible = self
+            .help_area(self.last_area, &text)
+            .height
+            .saturating_sub(2);
+        (text.height() as u16).saturating_sub(visible)
+    }
+
+    /// Draws the help overlay over `area`. Lines that don't fit can be scrolled to
+    /// with j/k; anything too wide is cut off rather than wrapped.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let text = self.help_text();
+        let popup = self.help_area(area, &text);
+
+        let block = Block::bordered()
+            .title(Line::from(" Key Bindings ".bold()).centered())
+            .title_bottom(Line::from(" <j/k> scroll, <?> / <Esc> close ").centered())
+            .border_set(border::THICK)
+            .padding(Padding::horizontal(1));
+
+        Clear.render(popup, buf);
+        Paragraph::new(text)
+            .block(block)
+            .scroll((self.help_scroll, 0))
+            .render(popup, buf);
+    }
+
+    /// Draws the `:load` menu over `area`: one line per slot with its size, the
+    /// highlighted one reversed.
+    fn render_slot_menu(&self, menu: &SlotMenu, area: Rect, buf: &mut Buffer) {
+        let name_width = menu
+            .slots
+            .iter()
+            .map(|slot| slot.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = menu
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(i, slot)| {
+                let line = Line::from(format!(
+                    "{:<name_width$}  {}x{}",
+                    slot.name, slot.width, slot.height
+                ));
+                if i == menu.selected {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        let text = Text::from(lines);
+        let hint = Line::from(" <j/k> move, <Enter> load, <Esc> close ");
+        let popup = popup_area(area, text.width().max(hint.width()), text.height());
+
+        // Keep the highlighted slot in view on short terminals
+        let visible = popup.height.saturating_sub(2) as usize;
+        let scroll = (menu.selected + 1).saturating_sub(visible) as u16;
+
+        let block = Block::bordered()
+            .title(Line::from(" Saved Slots ".bold()).centered())
+            .title_bottom(hint.centered())
+            .border_set(border::THICK)
+            .padding(Padding::horizontal(1));
+
+        Clear.render(popup, buf);
+        Paragraph::new(text)
+            .block(block)
+            .scroll((scroll, 0))
+            .render(popup, buf);
+    }
+
+    /// The status line under the board: cursor position, population, density,
+    /// block entropy, generation and speed.
+    fn status_line(&self) -> Line<'static> {
+        let (row, col) = self.cursor_pos;
+        let mut spans = vec![
+            format!(" [ROW {row} COL {col}]").bold(),
+            format!(" [POP {}]", self.grid.population()).into(),
+            format!(" [DENSITY {:.1}%]", self.grid.density() * 100.0).into(),
+        ];
+        if let Some((_, entropy)) = self.entropy {
+            spans.push(format!(" [ENTROPY {entropy:.2}]").into());
+        }
+        spans.push(format!(" [GEN {}]", self.grid.generation()).into());
+        if let Some((left, _)) = self.run_for {
+            spans.push(format!(" [{left} LEFT]").into());
+        }
+        if let Some(watch) = self.watch {
+            spans.push(format!(" [WATCH {watch}]").into());
+        }
+        if self.grid.backend() == Backend::Sparse {
+            spans.push(" [SPARSE]".into());
+        }
+        let history_bytes = self.history_bytes();
+        if history_bytes > 0 {
+            spans.push(format!(" [HISTORY {}]", format_bytes(history_bytes)).into());
+        }
+        spans.push(format!(" {} ", self.speed_label()).into());
+        Line::from(spans)
+    }
+
+    /// Memory held by the undo, redo and step-back snapshots, in bytes.
+    fn history_bytes(&self) -> usize {
+        let past: usize = self
+            .past_generations
+            .iter()
+            .map(GridSnapshot::byte_size)
+            .sum();
+        self.history.byte_size() + past
+    }
+
+    /// The currently active color theme.
+    fn theme(&self) -> &'static Theme {
+        &THEMES[self.theme_index]
+    }
+
+    /// Check if a cell falls inside the visual selection box
+    fn is_in_selection(&self, r: usize, c: usize) -> bool {
+        if self.mode != Mode::VISUAL {
+            return false;
+        }
+        match self.selection_anchor {
+            Some((anchor_r, anchor_c)) => {
+                let (cursor_r, cursor_c) = self.cursor_pos;
+                let (min_r, max_r, min_c, max_c) =
+                    get_row_and_col_span(cursor_r, cursor_c, anchor_r, anchor_c);
+
+                r >= min_r && r <= max_r && c >= min_c && c <= max_c
+            }
+            None => false,
+        }
+    }
+
+    /// Check if a cell falls inside the pinned analysis region
+    fn is_in_pinned_region(&self, r: usize, c: usize) -> bool {
+        match self.pinned_region {
+            Some((min_r, max_r, min_c, max_c)) => {
+                r >= min_r && r <= max_r && c >= min_c && c <= max_c
+            }
+            None => false,
+        }
+    }
+
+    /// Title bar readout for the VISUAL selection and pinned region: live cells inside
+    /// each over its area.
+    fn region_label(&self) -> String {
+        let count = |(min_r, max_r, min_c, max_c): CellRect| {
+            let alive = self.grid.population_in(min_r, max_r, min_c, max_c);
+            let area = (max_r - min_r + 1) * (max_c - min_c + 1);
+            format!("{alive}/{area}")
+        };
+        let mut labels = Vec::new();
+        if let Some(selection) = self.selection_rect() {
+            labels.push(format!("[SELECTION {}]", count(selection)));
+        }
+        if let Some(region) = self.pinned_region {
+            labels.push(format!("[REGION {}]", count(region)));
+        }
+        labels.join(" ")
+    }
+
+    /// Title bar readout naming the oscillator period once the board starts
+    /// repeating, or the period and move of a board that repeats shifted (a lone
+    /// spaceship). Still lifes (period 1) pause the simulation instead.
+    fn cycle_label(&self) -> String {
+        match self.grid.detect_cycle(CYCLE_HISTORY_LEN) {
+            Some(period) if period > 1 => format!("[PERIOD {period}]"),
+            Some(_) => String::new(),
+            None => match self.grid.detect_spaceship(CYCLE_HISTORY_LEN) {
+                Some(spaceship) => format!("[{spaceship}]"),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// The VISUAL mode selection between the anchor and the cursor, if any.
+    fn selection_rect(&self) -> Option<CellRect> {
+        match (self.mode == Mode::VISUAL, self.selection_anchor) {
+            (true, Some((anchor_r, anchor_c))) => Some(get_row_and_col_span(
+                self.cursor_pos.0,
+                self.cursor_pos.1,
+                anchor_r,
+                anchor_c,
+            )),
+            _ => None,
+        }
+    }
+
+    /// True if the cursor should be drawn on (r, c).
+    fn is_cursor(&self, r: usize, c: usize) -> bool {
+        (r, c) == self.cursor_pos && self.mode != Mode::RUNNING
+    }
+
+    /// Heatmap color for a live cell, if the heatmap is active.
+    fn heatmap_color(&self, r: usize, c: usize) -> Option<Color> {
+        self.neighbor_counts
+            .as_ref()
+            .map(|counts| HEATMAP_COLORS[counts[r * self.grid.width + c]])
+    }
+
+    /// Age color for a live cell, if age colors are on.
+    fn age_color(&self, r: usize, c: usize) -> Option<Color> {
+        if !self.age_colors {
+            return None;
+        }
+        let age = self.grid.age(r, c);
+        AGE_COLORS
+            .iter()
+            .rev()
+            .find(|&&(threshold, _)| age >= threshold)
+            .map(|&(_, color)| color)
+    }
+
+    /// Preview color for a cell that will be born or die next generation, if the
+    /// preview is showing.
+    fn change_color(&self, r: usize, c: usize) -> Option<Color> {
+        let next_states = self.next_states.as_ref()?;
+        let current = self.grid.get_copy(r, c)?;
+        match (current, next_states[r * self.grid.width + c]) {
+            (CellState::Dead, CellState::Alive) => Some(self.theme().birth),
+            (CellState::Alive, CellState::Dead) => Some(self.theme().death),
+            _ => None,
+        }
+    }
+
+    /// Number of cached display rows: one per grid row, except half-block mode
+    /// which packs two grid rows into each.
+    fn display_rows(&self) -> usize {
+        match self.render_style {
+            RenderStyle::Standard | RenderStyle::DoubledRows => self.grid.height,
+            RenderStyle::HalfBlock => self.grid.height.div_ceil(2),
+        }
+    }
+
+    /// Number of terminal rows each display row is drawn on.
+    fn lines_per_display_row(&self) -> usize {
+        match self.render_style {
+            RenderStyle::DoubledRows => 2,
+            RenderStyle::Standard | RenderStyle::HalfBlock => 1,
+        }
+    }
+
+    /// The grid rows shown by display row `i`.
+    fn display_row_grid_rows(&self, i: usize) -> std::ops::Range<usize> {
+        match self.render_style {
+            RenderStyle::Standard | RenderStyle::DoubledRows => i..i + 1,
+            RenderStyle::HalfBlock => 2 * i..(2 * i + 2).min(self.grid.height),
+        }
+    }
+
+    /// Everything that determines how display row `i` looks. If this matches the
+    /// key cached from the previous frame, the cached `Line` can be reused as is.
+    /// The cells (and the heatmap, preview and ages drawn from them) are covered by
+    /// the grid's revision, so building a key never looks at the cells themselves.
+    fn display_row_key(&self, i: usize, selection: Option<CellRect>) -> RowKey {
+        let rows = self.display_row_grid_rows(i);
+        let cursor =
+            Some(self.cursor_pos).filter(|&(r, c)| self.is_cursor(r, c) && rows.contains(&r));
+        let clip = |span: Option<CellRect>| {
+            span.filter(|&(min_r, max_r, _, _)| min_r < rows.end && max_r >= rows.start)
+                .map(|(min_r, max_r, min_c, max_c)| {
+                    (min_r.max(rows.start), max_r.min(rows.end - 1), min_c, max_c)
+                })
+        };
+
+        RowKey {
+            revision: self.grid.revision(),
+            heatmap: self.neighbor_counts.is_some(),
+            preview: self.next_states.is_some(),
+            age_colors: self.age_colors,
+            cursor,
+            selection: clip(selection),
+            pinned_region: clip(self.pinned_region),
+        }
+    }
+
+    /// Builds display row `i` in the current render style.
+    fn build_display_row(&self, i: usize) -> Line<'static> {
+        match self.render_style {
+            RenderStyle::Standard | RenderStyle::DoubledRows => self.full_cell_line(i),
+            RenderStyle::HalfBlock => self.half_block_line(2 * i),
+        }
+    }
+
+    /// Builds the `Line` for grid row `r` using two-column `██`/`░░` cells
+    /// (Standard and DoubledRows styles).
+    fn full_cell_line(&self, r: usize) -> Line<'static> {
+        let theme = self.theme();
+        let mut row_spans = Vec::with_capacity(self.grid.width);
+
+        for c in 0..self.grid.width {
+            let alive = self.grid.get_copy(r, c) == Some(CellState::Alive);
+
+            // Determine the character symbol (Block for Alive, Dotted for Dead)
+            let symbol = if alive { "██" } else { "░░" };
+
+            // Apply styling (Colors from the theme) based on state:
+            // 1. Cursor position
+            // 2. Selection area
+            // 3. Change preview (if enabled, cells about to be born or die)
+            // 4. Heatmap gradient (if enabled, live cells only)
+            // 5. Age gradient (if enabled, live cells only)
+            // 6. Normal cell
+            let style = if self.is_cursor(r, c) {
+                Style::default().fg(theme.cursor_fg).bg(theme.cursor_bg)
+            } else if self.is_in_selection(r, c) {
+                if alive {
+                    Style::default()
+                        .bg(theme.selection_fg)
+                        .fg(theme.selection_bg)
+                } else {
+                    Style::default()
+                        .bg(theme.selection_bg)
+                        .fg(theme.selection_fg)
+                }
+            } else if let Some(color) = self.change_color(r, c) {
+                Style::default().fg(color)
+            } else if let Some(color) = self.heatmap_color(r, c).filter(|_| alive) {
+                Style::default().fg(color)
+            } else if let Some(color) = self.age_color(r, c).filter(|_| alive) {
+                Style::default().fg(color)
+            } else if alive {
+                Style::default().fg(theme.alive)
+            } else {
+                Style::default().fg(theme.dead)
+            };
+            // 7. Pinned region tints the background of anything not already highlighted
+            let style = if style.bg.is_none() && self.is_in_pinned_region(r, c) {
+                style.bg(theme.region)
+            } else {
+                style
+            };
+
+            row_spans.push(Span::styled(symbol, style));
+        }
+
+        Line::from(row_spans)
+    }
+
+    /// Solid color of a single cell in half-block mode, where each character
+    /// can only show one color per cell.
+    fn half_block_color(&self, r: usize, c: usize) -> Color {
+        let theme = self.theme();
+        let alive = self.grid.get_copy(r, c) == Some(CellState::Alive);
+
+        if self.is_cursor(r, c) {
+            if alive {
+                theme.half_cursor_alive
+            } else {
+                theme.half_cursor_dead
+            }
+        } else if self.is_in_selection(r, c) {
+            if alive {
+                theme.selection_bg
+            } else {
+                theme.half_selection_dead
+            }
+        } else if let Some(color) = self.change_color(r, c) {
+            color
+        } else if alive {
+            self.heatmap_color(r, c)
+                .or_else(|| self.age_color(r, c))
+                .unwrap_or(theme.alive)
+        } else if self.is_in_pinned_region(r, c) {
+            theme.region
+        } else {
+            theme.empty
+        }
+    }
+
+    /// Builds a line showing grid rows `top_r` and `top_r + 1`: the upper half-block
+    /// `▀` is painted with the top cell's color (fg) over the bottom cell's color (bg).
+    /// Each cell is a single column wide, so cells come out roughly square.
+    fn half_block_line(&self, top_r: usize) -> Line<'static> {
+        let bottom_r = top_r + 1;
+        let mut row_spans = Vec::with_capacity(self.grid.width);
+
+        for c in 0..self.grid.width {
+            let top = self.half_block_color(top_r, c);
+            // An odd-height grid leaves the last bottom half empty
+            let bottom = if bottom_r < self.grid.height {
+                self.half_block_color(bottom_r, c)
+            } else {
+                Color::Reset
+            };
+
+            row_spans.push(Span::styled("▀", Style::default().fg(top).bg(bottom)));
+        }
+
+        Line::from(row_spans)
+    }
+
+    /// Horizontal placement of the grid lines inside `inner`: the starting column and
+    /// the visible width. Lines are centered, and fill the width if they don't fit.
+    fn grid_line_origin(&self, inner: Rect) -> (u16, u16) {
+        let line_width = (self.grid.width as u16)
+            .saturating_mul(self.render_style.cell_columns())
+            .min(inner.width);
+        let line_x = inner.x + (inner.width / 2).saturating_sub(line_width / 2);
+        (line_x, line_width)
+    }
+
+    /// Draws the grid into `inner`, rebuilding only the display rows whose `RowKey`
+    /// changed since the last frame and reusing the cached `Line` for the rest.
+    /// Only the rows and columns inside the scrolled view are drawn.
+    fn render_grid(&self, inner: Rect, buf: &mut Buffer) {
+        let (line_x, line_width) = self.grid_line_origin(inner);
+        let lines_per_row = self.lines_per_display_row();
+        let selection = self.selection_rect();
+
+        let mut cache = self.line_cache.borrow_mut();
+        let settings = (
+            self.render_style,
+            self.theme_index,
+            self.grid.width,
+            self.grid.height,
+        );
+        if cache.settings != Some(settings) {
+            // Anything cached was drawn with different settings; start over
+            cache.settings = Some(settings);
+            cache.rows = vec![None; self.display_rows()];
+        }
+
+        let cell_columns = self.render_style.cell_columns();
+        // Display rows are cached for the whole grid; the view starts at the one
+        // holding `scroll_row`
+        let first_row = match self.render_style {
+            RenderStyle::Standard | RenderStyle::DoubledRows => self.scroll_row,
+            RenderStyle::HalfBlock => self.scroll_row / 2,
+        };
+
+        for i in first_row..self.display_rows() {
+            let y = inner.y as usize + (i - first_row) * lines_per_row;
+            if y >= inner.bottom() as usize {
+                break;
+            }
+
+            let key = self.display_row_key(i, selection);
+            let slot = &mut cache.rows[i];
+            if slot.as_ref().is_none_or(|cached| cached.key != key) {
+                *slot = Some(CachedLine {
+                    line: self.build_display_row(i),
+                    key,
+                });
+            }
+
+            // Each span is one cell, so skipping `scroll_col` spans scrolls horizontally
+            if let Some(cached) = slot {
+                for line_y in (y..y + lines_per_row).filter(|&ly| ly < inner.bottom() as usize) {
+                    let mut x = line_x;
+                    for span in cached.line.spans.iter().skip(self.scroll_col) {
+                        let remaining = line_x + line_width - x;
+                        if remaining == 0 {
+                            break;
+                        }
+                        buf.set_span(x, line_y as u16, span, remaining);
+                        x += cell_columns.min(remaining);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A rectangle of grid cells as (min_row, max_row, min_col, max_col).
+type CellRect = (usize, usize, usize, usize);
+
+/// The inputs that determine how one display row is drawn. Plain values only, so
+/// building one per row every frame costs no allocation.
+#[derive(Clone, Copy, PartialEq)]
+struct RowKey {
+    revision: u64,                   // `Grid::revision` the line was drawn from
+    heatmap: bool,                   // Heatmap counts shown
+    preview: bool,                   // Next-generation preview shown
+    age_colors: bool,                // Live cells colored by age
+    cursor: Option<(usize, usize)>,  // Cursor position, if visible on this line
+    selection: Option<CellRect>,     // Visual selection clipped to this line's rows
+    pinned_region: Option<CellRect>, // Pinned region clipped to this line's rows
+}
+
+/// A display row drawn in a previous frame, with the key it was drawn from.
+#[derive(Clone)]
+struct CachedLine {
+    key: RowKey,
+    line: Line<'static>,
+}
+
+/// Lines from the previous frame, reused while their `RowKey` is unchanged.
+/// Invalidated wholesale whenever the render style, theme or grid size changes.
+#[derive(Default)]
+struct LineCache {
+    settings: Option<(RenderStyle, usize, usize, usize)>,
+    rows: Vec<Option<CachedLine>>,
+}
+
+/// The main UI rendering logic.
+/// Ratatui calls this to paint the `App` onto the `Frame`.
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Bail out early with a notice on terminals too small to be usable.
+        // This recovers by itself once the terminal is enlarged, since every frame re-checks.
+        if area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT {
+            render_too_small(area, buf);
+            return;
+        }
+
+        // Construct the title bar
+        let title = Line::from(
+            format!(
+                " Conway's Game of Rust {} {} [{}] [{}] {} {} {}",
+                self.mode,
+                self.cycle_label(),
+                self.theme().name,
+                self.grid.boundary_mode(),
+                self.symmetry,
+                self.region_label(),
+                self.pending_label()
+            )
+            .bold(),
+        );
+
+        // Dynamic help text at the bottom based on current mode, replaced by the
+        // prompt while typing a command and by the result of one just after
+        let instructions = if self.mode == Mode::COMMAND {
+            Line::from(format!(" :{}█ ", self.command))
+        } else if let Some(run) = &self.fast_forward {
+            Line::from(format!(" {} <Esc> to stop ", progress_bar(run.done, run.total)).bold())
+        } else if !self.status.is_empty() {
+            Line::from(format!(" {} ", self.status).bold())
+        } else {
+            self.instructions_line()
+        };
+
+        // Create the border block
+        let block = Block::bordered()
+            .title(title.centered())
+            .title_bottom(instructions.centered())
+            .border_set(border::THICK);
+
+        // --- Render the Grid ---
+        let (board, status) = split_status_line(area);
+        let inner = block.inner(board);
+        block.render(board, buf);
+        self.render_grid(inner, buf);
+
+        if let Some(status) = status {
+            self.status_line().render(status, buf);
+        }
+
+        if let Some(menu) = &self.slot_menu {
+            self.render_slot_menu(menu, area, buf);
+        }
+        if self.show_help {
+            self.render_help(area, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Option<Options>, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// Types `input` at the `:` prompt and presses Enter.
+    fn command(app: &mut App, input: &str) {
+        app.mode = Mode::COMMAND;
+        for ch in input.chars() {
+            app.handle_command_key(KeyEvent::from(KeyCode::Char(ch)));
+        }
+        app.handle_command_key(KeyEvent::from(KeyCode::Enter));
+    }
+
+    fn blinker(width: usize, height: usize) -> Grid {
+        let mut pattern = Grid::new(width, height);
+        pattern.set_alive(&[(height / 2, 0), (height / 2, 1), (height / 2, 2)]);
+        pattern
+    }
+
+    #[test]
+    fn oversized_patterns_grow_the_board_or_are_clipped() {
+        let pattern = blinker(3, 1);
+        let (grid, note) = fit_pattern(&pattern, 5, 5, LoadFit::Resize).unwrap();
+        assert_eq!((grid.width, grid.height), (5, 5));
+        assert_eq!(grid.population(), 3);
+        assert_eq!(note, None);
+
+        let (grid, note) = fit_pattern(&pattern, 2, 5, LoadFit::Resize).unwrap();
+        assert_eq!((grid.width, grid.height), (3, 5));
+        assert_eq!(grid.population(), 3);
+        assert_eq!(note.as_deref(), Some("board grown to 3x5 to fit"));
+
+        let (grid, note) = fit_pattern(&pattern, 1, 5, LoadFit::Clip).unwrap();
+        assert_eq!((grid.width, grid.height), (1, 5));
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(2, 0)]);
+        assert!(note
+            .unwrap()
+            .contains("3x1 pattern clipped to the 1x5 board, 2 live cells lost"));
+    }
+
+    #[test]
+    fn set_fit_is_parsed_and_applied() {
+        let mut app = App::default();
+        assert_eq!(app.load_fit, LoadFit::Resize);
+        command(&mut app, "set fit clip");
+        assert_eq!(app.load_fit, LoadFit::Clip);
+        assert_eq!(app.status, "fit = clip");
+        command(&mut app, "set fit squash");
+        assert_eq!(app.load_fit, LoadFit::Clip);
+        command(&mut app, "set nothing");
+        assert_eq!(app.status, "Unknown setting: nothing");
+    }
+
+    #[test]
+    fn options_accept_values_inline_or_separately() {
+        let options = parse_args(&["--width=64", "--height", "32", "--wrap", "board.rle"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.width, Some(64));
+        assert_eq!(options.height, Some(32));
+        assert!(options.wrap);
+        assert_eq!(options.file.as_deref(), Some("board.rle"));
+        assert!(parse_args(&["--help"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn options_only_split_flags_on_equals() {
+        let options = parse_args(&["a=b.rle"]).unwrap().unwrap();
+        assert_eq!(options.file.as_deref(), Some("a=b.rle"));
+        assert!(parse_args(&["-q=1"]).is_err());
+    }
+
+    #[test]
+    fn options_reject_values_on_switches() {
+        assert_eq!(
+            parse_args(&["--wrap=x"]).err().as_deref(),
+            Some("--wrap doesn't take a value")
+        );
+        assert!(parse_args(&["--quiet=yes"]).is_err());
+        assert!(parse_args(&["--help=1"]).is_err());
+    }
+
+    #[test]
+    fn options_reject_bad_sizes_and_extra_files() {
+        assert!(parse_args(&["--width=0"]).is_err());
+        assert!(parse_args(&["--height", "tall"]).is_err());
+        assert!(parse_args(&["--width"]).is_err());
+        assert!(parse_args(&["one.rle", "two.rle"]).is_err());
+        assert!(parse_args(&["--frobnicate"]).is_err());
+    }
+
+    fn mouse_down(app: &mut App, column: u16, row: u16) {
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Up(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+    }
+
+    #[test]
+    fn repeated_half_block_clicks_alternate_rows() {
+        let mut app = App {
+            grid: Grid::new(10, 10),
+            render_style: RenderStyle::HalfBlock,
+            cursor_pos: (5, 5),
+            last_area: Rect::new(0, 0, 80, 24),
+            ..App::default()
+        };
+        // Ten one-column cells centered in the 78-column inner area start at column 35,
+        // and the first text row (1) shows grid rows 0 and 1
+        mouse_down(&mut app, 37, 1);
+        assert_eq!(app.cursor_pos, (0, 2));
+        mouse_down(&mut app, 37, 1);
+        assert_eq!(app.cursor_pos, (1, 2));
+        mouse_down(&mut app, 37, 1);
+        assert_eq!(app.cursor_pos, (0, 2));
+        // Toggled twice and once respectively
+        assert_eq!(app.grid.get_copy(0, 2), Some(CellState::Dead));
+        assert_eq!(app.grid.get_copy(1, 2), Some(CellState::Alive));
+    }
+
+    #[test]
+    fn history_undoes_and_redoes_edits() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut grid = Grid::new(4, 4);
+
+        history.record(&grid);
+        grid.set(0, 0, CellState::Alive);
+        history.record(&grid);
+        grid.set(1, 1, CellState::Alive);
+        let edited = grid.clone();
+
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 1);
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.population(), 0);
+        assert!(!history.undo(&mut grid));
+
+        assert!(history.redo(&mut grid));
+        assert!(history.redo(&mut grid));
+        assert_eq!(grid, edited);
+        assert!(!history.redo(&mut grid));
+    }
+
+    #[test]
+    fn history_drops_redo_on_a_new_edit_and_caps_its_length() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut grid = Grid::new(4, 4);
+        history.record(&grid);
+        grid.set(0, 0, CellState::Alive);
+        assert!(history.undo(&mut grid));
+
+        history.record(&grid);
+        assert!(!history.redo(&mut grid));
+
+        for _ in 0..HISTORY_LIMIT + 10 {
+            history.record(&grid);
+        }
+        assert_eq!(history.undo.len(), HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn history_restores_the_size_before_a_resize() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut grid = Grid::new(4, 4);
+        grid.set(3, 3, CellState::Alive);
+        history.record(&grid);
+        grid.resize(2, 2).unwrap();
+
+        assert!(history.undo(&mut grid));
+        assert_eq!((grid.width, grid.height), (4, 4));
+        assert_eq!(grid.get_copy(3, 3), Some(CellState::Alive));
+    }
+
+    #[test]
+    fn shrinking_the_board_clamps_the_cursor_and_selection() {
+        let mut app = App {
+            grid: Grid::new(20, 20),
+            ..App::default()
+        };
+        app.cursor_pos = (15, 18);
+        app.selection_anchor = Some((19, 2));
+        command(&mut app, "resize 10 8");
+        assert_eq!(app.cursor_pos, (7, 9));
+        assert_eq!(app.selection_anchor, Some((7, 2)));
+
+        // Toggling where the cursor now sits lands on the board
+        press(&mut app, " ");
+        assert_eq!(app.grid.get_copy(7, 9), Some(CellState::Alive));
+    }
+
+    #[test]
+    fn peak_population_counts_every_generation() {
+        let mut app = App {
+            grid: Grid::new(8, 8),
+            ..App::default()
+        };
+        // The R-pentomino grows, so the peak comes from a generation, not the start
+        app.grid.load_pattern(".##\n##.\n.#.", 3, 3);
+        let start = app.grid.population();
+        let mut peak = start;
+        for _ in 0..5 {
+            app.advance_generation();
+            peak = peak.max(app.grid.population());
+        }
+        assert!(peak > start);
+        assert_eq!(app.stats.peak_population, peak);
+        assert_eq!(app.stats.generations, 5);
+    }
+
+    #[test]
+    fn peak_population_counts_generations_inside_a_run() {
+        let mut app = App {
+            grid: Grid::new(8, 8),
+            ..App::default()
+        };
+        app.grid.load_pattern(".##\n##.\n.#.", 3, 3);
+        let mut expected = app.grid.clone();
+        let mut peak = expected.population();
+        for _ in 0..30 {
+            expected.next_generation();
+            peak = peak.max(expected.population());
+        }
+
+        command(&mut app, "run 30");
+        while app.fast_forward.is_some() {
+            app.run_chunk();
+        }
+        // Crowded by the small board, the pentomino peaks partway and shrinks again
+        assert!(peak > app.grid.population());
+        assert_eq!(app.stats.peak_population, peak);
+    }
+
+    #[test]
+    fn status_line_shows_density_and_entropy() {
+        let mut app = App {
+            grid: Grid::new(4, 4),
+            ..App::default()
+        };
+        app.refresh_entropy();
+        let status = app.status_line().to_string();
+        assert!(status.contains("[DENSITY 0.0%]"), "{status}");
+        assert!(status.contains("[ENTROPY 0.00]"), "{status}");
+
+        app.grid.fill_region(0, 1, 0, 3, CellState::Alive);
+        app.refresh_entropy();
+        let status = app.status_line().to_string();
+        assert!(status.contains("[DENSITY 50.0%]"), "{status}");
+        assert!(status.contains("[ENTROPY 1.00]"), "{status}");
+    }
+
+    #[test]
+    fn speed_presets_are_reachable_with_plus_and_minus() {
+        assert!(SPEED_PRESETS
+            .iter()
+            .all(|ms| (MIN_TICK_MS..=MAX_TICK_MS).contains(ms)));
+        assert!(SPEED_PRESETS.windows(2).all(|pair| pair[0] > pair[1]));
+        assert_eq!(SPEED_PRESETS[4], TIME_BETWEEN_GENERATIONS);
+    }
+
+    #[test]
+    fn terminal_positions_map_to_double_width_cells() {
+        let mut app = App {
+            grid: Grid::new(10, 10),
+            last_area: Rect::new(0, 0, 80, 24),
+            ..App::default()
+        };
+        // Ten two-column cells centered in the 78-column inner area start at column 30;
+        // the inner area starts below the top border, at row 1
+        assert_eq!(app.terminal_to_grid(30, 1), Some((0, 0)));
+        assert_eq!(app.terminal_to_grid(31, 1), Some((0, 0)));
+        assert_eq!(app.terminal_to_grid(32, 1), Some((0, 1)));
+        assert_eq!(app.terminal_to_grid(49, 10), Some((9, 9)));
+        // Either side of the grid, below its last row, and on the border
+        assert_eq!(app.terminal_to_grid(29, 1), None);
+        assert_eq!(app.terminal_to_grid(50, 1), None);
+        assert_eq!(app.terminal_to_grid(30, 11), None);
+        assert_eq!(app.terminal_to_grid(0, 0), None);
+
+        // Scrolled views are offset by the scroll position
+        app.scroll_row = 2;
+        app.scroll_col = 3;
+        assert_eq!(app.terminal_to_grid(30, 1), Some((2, 3)));
+        assert_eq!(app.terminal_to_grid(43, 7), Some((8, 9)));
+        assert_eq!(app.terminal_to_grid(44, 7), None);
+
+        // Nothing is drawn in a terminal below the minimum size
+        app.last_area = Rect::new(0, 0, MIN_TERMINAL_WIDTH - 1, 24);
+        assert_eq!(app.terminal_to_grid(30, 1), None);
+    }
+
+    #[test]
+    fn yanked_selection_pastes_at_the_cursor() {
+        let mut app = App {
+            grid: Grid::new(8, 8),
+            ..App::default()
+        };
+        app.grid.set_alive(&[(0, 0), (1, 1)]);
+        for ch in ['v', 'l', 'j', 'y', '4', 'l', '5', 'j', 'p'] {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(ch)));
+        }
+        assert!(app.mode == Mode::NORMAL);
+        assert_eq!(
+            app.grid.live_cells().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (6, 5), (7, 6)]
+        );
+        // The paste can be undone
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('u')));
+        assert_eq!(app.grid.population(), 2);
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for ch in keys.chars() {
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(ch)));
+        }
+    }
+
+    #[test]
+    fn key_sequences_run_when_complete() {
+        let mut app = App {
+            grid: Grid::new(6, 6),
+            cursor_pos: (4, 2),
+            ..App::default()
+        };
+        app.grid.fill_region(0, 5, 0, 5, CellState::Alive);
+        press(&mut app, "d");
+        assert_eq!(app.pending_label(), "d");
+        assert_eq!(app.grid.population(), 36);
+        press(&mut app, "d");
+        assert_eq!(app.pending_label(), "");
+        assert_eq!(app.grid.population(), 30);
+        assert_eq!(app.grid.get_copy(4, 0), Some(CellState::Dead));
+
+        press(&mut app, "dc");
+        assert_eq!(app.grid.population(), 25);
+        press(&mut app, "gg");
+        assert_eq!(app.cursor_pos, (0, 2));
+    }
+
+    #[test]
+    fn broken_or_stale_sequences_are_discarded() {
+        let mut app = App {
+            grid: Grid::new(6, 6),
+            cursor_pos: (4, 2),
+            ..App::default()
+        };
+        // `gj` isn't a sequence: both keys are dropped, so the cursor stays put
+        press(&mut app, "gj");
+        assert_eq!(app.cursor_pos, (4, 2));
+        assert_eq!(app.pending_label(), "");
+
+        // Esc cancels, and is handled as usual
+        press(&mut app, "g");
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc));
+        press(&mut app, "g");
+        assert_eq!(app.cursor_pos, (4, 2));
+
+        // A sequence left waiting too long is dropped
+        app.pending_since = Some(Instant::now() - KEY_SEQUENCE_TIMEOUT);
+        app.expire_pending_keys();
+        press(&mut app, "g");
+        assert_eq!(app.cursor_pos, (4, 2));
+        assert_eq!(app.pending_label(), "g");
+    }
+
+    #[test]
+    fn counts_repeat_movements() {
+        let mut app = App {
+            grid: Grid::new(20, 20),
+            ..App::default()
+        };
+        press(&mut app, "12");
+        assert_eq!(app.pending_label(), "12");
+        press(&mut app, "j");
+        assert_eq!(app.cursor_pos, (12, 0));
+        // Clamped at the edge
+        press(&mut app, "30l");
+        assert_eq!(app.cursor_pos, (12, 19));
+        // `0` on its own is a motion, not a count
+        press(&mut app, "0");
+        assert_eq!(app.cursor_pos, (12, 0));
+    }
+
+    #[test]
+    fn history_limit_drops_the_oldest_snapshots() {
+        // Kept dense, so every snapshot of the empty board is the same size
+        let mut app = App {
+            grid: Grid::new(16, 16),
+            auto_backend: false,
+            ..App::default()
+        };
+        for _ in 0..10 {
+            press(&mut app, "n");
+        }
+        assert_eq!(app.history.undo.len(), 10);
+        assert_eq!(app.past_generations.len(), 10);
+        // Sixteen rows of sixteen cells, in whichever store is compiled in
+        let snapshot_bytes = app.grid.snapshot().byte_size();
+        assert_eq!(app.history_bytes(), 20 * snapshot_bytes);
+
+        command(&mut app, "set history 4");
+        assert_eq!(app.history.undo.len(), 4);
+        assert_eq!(app.past_generations.len(), 4);
+        assert!(app.status.starts_with("history = 4 ("), "{}", app.status);
+        for _ in 0..3 {
+            press(&mut app, "n");
+        }
+        assert_eq!(app.history.undo.len(), 4);
+        assert_eq!(app.past_generations.len(), 4);
+
+        // Zero turns both off
+        command(&mut app, "set history 0");
+        press(&mut app, "n");
+        assert_eq!(app.history_bytes(), 0);
+        assert!(!app.status_line().to_string().contains("[HISTORY"));
+
+        command(&mut app, "set history lots");
+        assert_eq!(app.status, "Invalid snapshot count: lots");
+    }
+
+    #[test]
+    fn byte_counts_are_shown_in_the_largest_fitting_unit() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(200 << 20), "200.0 MB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GB");
+    }
+
+    #[test]
+    fn options_take_a_history_depth() {
+        let options = parse_args(&["--history", "5"]).unwrap().unwrap();
+        assert_eq!(options.history, Some(5));
+        assert!(parse_args(&["--history=-1"]).is_err());
+    }
+
+    #[test]
+    fn title_names_a_lone_spaceship() {
+        let mut app = App {
+            grid: Grid::new(20, 20),
+            ..App::default()
+        };
+        app.grid.stamp_pattern(NamedPattern::Glider, 2, 2);
+        assert_eq!(app.cycle_label(), "");
+        for _ in 0..4 {
+            app.advance_generation();
+        }
+        assert_eq!(app.cycle_label(), "[glider: period 4, moves (1,1)]");
+    }
+
+    #[test]
+    fn zz_centers_the_view_on_the_cursor() {
+        // The 78x21 inner area shows 39 two-column cells across and 21 rows
+        let mut app = App {
+            grid: Grid::new(200, 100),
+            cursor_pos: (50, 100),
+            last_area: Rect::new(0, 0, 80, 24),
+            ..App::default()
+        };
+        app.scroll_to_cursor(grid_inner_area(app.last_area));
+        assert_eq!((app.scroll_row, app.scroll_col), (30, 62));
+        press(&mut app, "zz");
+        assert_eq!((app.scroll_row, app.scroll_col), (40, 81));
+        // Drawing again doesn't undo it
+        app.scroll_to_cursor(grid_inner_area(app.last_area));
+        assert_eq!((app.scroll_row, app.scroll_col), (40, 81));
+
+        // Near the edges the view stops at the grid
+        app.cursor_pos = (2, 198);
+        press(&mut app, "zz");
+        assert_eq!((app.scroll_row, app.scroll_col), (0, 200 - 39));
+    }
+
+    /// A fresh, empty directory under the system temp dir for tests that touch files.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("conway-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn slots_are_saved_listed_and_loaded_from_the_menu() {
+        let dir = scratch_dir("slots");
+        let mut app = App {
+            grid: blinker(8, 8),
+            slot_dir: Some(dir.clone()),
+            ..App::default()
+        };
+
+        command(&mut app, "load");
+        assert_eq!(app.status, format!("No saved slots in {}", dir.display()));
+        assert!(app.slot_menu.is_none());
+
+        command(&mut app, "save spinner");
+        assert_eq!(app.status, "Saved slot spinner");
+        app.grid.stamp_pattern(NamedPattern::Glider, 0, 0);
+        command(&mut app, "save arrow");
+        std::fs::write(dir.join("broken.rle"), "not a pattern").unwrap();
+
+        command(&mut app, "load");
+        let menu = app.slot_menu.as_ref().expect("menu opens");
+        let listed: Vec<_> = menu
+            .slots
+            .iter()
+            .map(|slot| (slot.name.as_str(), slot.width, slot.height))
+            .collect();
+        assert_eq!(listed, [("arrow", 3, 5), ("spinner", 3, 1)]);
+
+        app.grid.reset();
+        press(&mut app, "jjk");
+        assert_eq!(app.slot_menu.as_ref().unwrap().selected, 0);
+        press(&mut app, "j");
+        app.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(app.slot_menu.is_none());
+        assert_eq!(app.status, "Loaded slot spinner");
+        assert_eq!(app.grid.population(), 3);
+        assert_eq!((app.grid.width, app.grid.height), (8, 8));
+
+        command(&mut app, "load arrow");
+        assert_eq!(app.grid.population(), 8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn slot_commands_reject_paths_and_missing_names() {
+        let mut app = App {
+            slot_dir: Some(scratch_dir("unused")),
+            ..App::default()
+        };
+        command(&mut app, "save ../escape");
+        assert_eq!(app.status, "Invalid slot name: ../escape");
+        command(&mut app, "save");
+        assert_eq!(app.status, "Usage: :save <name>");
+        command(&mut app, "load nothing");
+        assert!(app.status.starts_with("Can't load slot nothing"));
+        assert!(matches!(
+            Command::parse("e board.txt"),
+            Ok(Some(Command::Load(path))) if path == "board.txt"
+        ));
+    }
+
+    #[test]
+    fn restore_loads_the_autosaved_board_or_falls_back() {
+        let dir = scratch_dir("session");
+        let session = dir.join(SESSION_FILE);
+        let options = parse_args(&["--restore"]).unwrap().unwrap();
+
+        let (grid, note) = options.grid(Some(&session)).unwrap();
+        assert_eq!((grid.width, grid.height), (128, 80));
+        assert_eq!(
+            note.as_deref(),
+            Some("Couldn't restore the last session: no session saved yet")
+        );
+
+        save_session(&session, &blinker(6, 5)).unwrap();
+        let (grid, note) = options.grid(Some(&session)).unwrap();
+        assert_eq!(grid.to_string(), blinker(6, 5).to_string());
+        assert_eq!(note, None);
+
+        std::fs::write(&session, "# . x\n").unwrap();
+        let (grid, note) = options.grid(Some(&session)).unwrap();
+        assert_eq!(grid.population(), 0);
+        assert!(note
+            .unwrap()
+            .starts_with("Couldn't restore the last session: can't load"));
+
+        // Without the flag the autosave is left alone
+        let (grid, note) = parse_args(&[])
+            .unwrap()
+            .unwrap()
+            .grid(Some(&session))
+            .unwrap();
+        assert_eq!((grid.population(), note), (0, None));
+        assert!(parse_args(&["--restore", "glider.rle"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn runfor_pauses_after_the_count_or_on_esc() {
+        let mut app = App {
+            grid: blinker(5, 5),
+            ..App::default()
+        };
+        command(&mut app, "runfor 3");
+        assert!(app.mode == Mode::RUNNING);
+        app.tick();
+        app.tick();
+        assert_eq!(app.run_for, Some((1, 3)));
+        assert!(app.mode == Mode::RUNNING);
+        app.tick();
+        assert!(app.mode == Mode::NORMAL);
+        assert_eq!(app.run_for, None);
+        assert_eq!(app.status, "Ran 3 generations");
+        assert_eq!(app.grid.generation(), 3);
+
+        command(&mut app, "runfor 10");
+        app.tick();
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(app.mode == Mode::NORMAL);
+        assert_eq!(app.run_for, None);
+        assert_eq!(app.grid.generation(), 4);
+
+        command(&mut app, "runfor 0");
+        assert_eq!(app.status, "Not a positive number: 0");
+    }
+
+    #[test]
+    fn rule_command_takes_presets_and_rulestrings() {
+        let mut app = App::default();
+        command(&mut app, "rule replicator");
+        assert_eq!(app.status, "rule = B1357/S1357 (replicator)");
+        command(&mut app, "rule b36/s23");
+        assert_eq!(app.status, "rule = B36/S23 (highlife)");
+        command(&mut app, "rule B2/S1");
+        command(&mut app, "rule");
+        assert!(app
+            .status
+            .starts_with("rule = B2/S1 (presets: conway, highlife"));
+        command(&mut app, "rule brain");
+        assert!(app.status.starts_with("Not a preset or rulestring: brain"));
+    }
+
+    /// Opens the `:` prompt, types `input` and presses Tab.
+    fn type_and_tab(app: &mut App, input: &str) {
+        app.mode = Mode::COMMAND;
+        app.command.clear();
+        for ch in input.chars() {
+            app.handle_command_key(KeyEvent::from(KeyCode::Char(ch)));
+        }
+        tab(app);
+    }
+
+    /// Presses Tab at the `:` prompt.
+    fn tab(app: &mut App) {
+        app.handle_command_key(KeyEvent::from(KeyCode::Tab));
+    }
+
+    #[test]
+    fn tab_completes_and_cycles_command_names() {
+        let mut app = App::default();
+        type_and_tab(&mut app, "ru");
+        assert_eq!(app.command, "rule ");
+        tab(&mut app);
+        assert_eq!(app.command, "run ");
+        tab(&mut app);
+        assert_eq!(app.command, "runfor ");
+        tab(&mut app);
+        assert_eq!(app.command, "rule ");
+
+        // Typing starts a fresh completion from what's there now
+        for ch in "hi".chars() {
+            app.handle_command_key(KeyEvent::from(KeyCode::Char(ch)));
+        }
+        tab(&mut app);
+        assert_eq!(app.command, "rule highlife");
+
+        type_and_tab(&mut app, "place zz");
+        assert_eq!(app.command, "place zz");
+        type_and_tab(&mut app, "set f");
+        assert_eq!(app.command, "set fit ");
+    }
+
+    #[test]
+    fn tab_completes_file_paths() {
+        let dir = scratch_dir("complete");
+        std::fs::create_dir_all(dir.join("alps")).unwrap();
+        for file in ["alpha.rle", "beta.cells", ".alt"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        let dir_name = format!("{}/", dir.display());
+        let mut app = App::default();
+        type_and_tab(&mut app, &format!("e {dir_name}al"));
+        assert_eq!(app.command, format!("e {dir_name}alpha.rle"));
+        tab(&mut app);
+        assert_eq!(app.command, format!("e {dir_name}alps/"));
+
+        assert_eq!(
+            path_completions(&format!("{dir_name}.")),
+            [format!("{dir_name}.alt")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn goto_moves_the_cursor_into_view_and_clamps() {
+        let mut app = App {
+            grid: Grid::new(200, 100),
+            last_area: Rect::new(0, 0, 80, 24),
+            ..App::default()
+        };
+        command(&mut app, "goto 70 150");
+        assert_eq!((app.cursor_pos, app.status.as_str()), ((70, 150), ""));
+        app.scroll_to_cursor(grid_inner_area(app.last_area));
+        assert_eq!((app.scroll_row, app.scroll_col), (70 - 20, 150 - 38));
+
+        command(&mut app, "goto 500 3");
+        assert_eq!(app.cursor_pos, (99, 3));
+        assert_eq!(app.status, "Off the board, moved to row 99 col 3");
+
+        command(&mut app, "goto 5 x");
+        assert_eq!(app.status, "Not a row or column: x");
+        command(&mut app, "goto 5");
+        assert_eq!(app.status, "Usage: :goto <row> <col>");
+        assert_eq!(app.cursor_pos, (99, 3));
+    }
+
+    #[test]
+    fn set_loop_pauses_where_the_board_starts_repeating() {
+        // A T-tetromino settles into traffic lights, four blinkers of period 2
+        let mut grid = Grid::new(16, 16);
+        grid.load_pattern("###\n.#.", 7, 6);
+        let mut app = App {
+            grid,
+            ..App::default()
+        };
+        command(&mut app, "set loop on");
+        assert_eq!(app.status, "loop = on");
+
+        app.mode = Mode::RUNNING;
+        let mut ticks = 0;
+        while app.mode == Mode::RUNNING && ticks < 50 {
+            app.tick();
+            ticks += 1;
+        }
+        assert_eq!(app.status, "Entered period-2 loop at gen 9");
+        assert_eq!(app.grid.generation(), 11);
+
+        // Generation 9 is the first to come round again
+        let mut grid = Grid::new(16, 16);
+        grid.load_pattern("###\n.#.", 7, 6);
+        let boards: Vec<String> = (0..12)
+            .map(|_| {
+                let board = grid.to_string();
+                grid.next_generation();
+                board
+            })
+            .collect();
+        assert_eq!(boards[9], boards[11]);
+        assert_ne!(boards[8], boards[10]);
+
+        // Off, the same run keeps going
+        let mut grid = Grid::new(16, 16);
+        grid.load_pattern("###\n.#.", 7, 6);
+        app.grid = grid;
+        command(&mut app, "set loop off");
+        app.mode = Mode::RUNNING;
+        for _ in 0..20 {
+            app.tick();
+        }
+        assert!(app.mode == Mode::RUNNING);
+    }
+
+    #[test]
+    fn base64_pads_partial_groups() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(
+            base64_encode(b"x = 3, y = 1\n3o!"),
+            "eCA9IDMsIHkgPSAxCjNvIQ=="
+        );
+    }
+
+    thread_local! {
+        static COPIED: RefCell<String> = RefCell::default();
+    }
+
+    #[test]
+    fn shift_y_sends_the_board_as_rle_or_reports_why_not() {
+        let mut app = App {
+            grid: blinker(5, 5),
+            system_clipboard: |text| {
+                COPIED.with(|copied| *copied.borrow_mut() = text.to_string());
+                Ok(())
+            },
+            ..App::default()
+        };
+        press(&mut app, "Y");
+        let rle = app.grid.to_rle();
+        assert_eq!(COPIED.with(|copied| copied.borrow().clone()), rle);
+        assert_eq!(
+            app.status,
+            format!(
+                "Sent the board as RLE ({} bytes) to the terminal's clipboard (needs OSC 52)",
+                rle.len()
+            )
+        );
+
+        app.system_clipboard = |_| Err(io::Error::other("not running in a terminal"));
+        command(&mut app, "yank rle");
+        assert_eq!(
+            app.status,
+            "Can't copy to the clipboard: not running in a terminal"
+        );
+    }
+
+    #[test]
+    fn loading_keeps_the_universe_unless_rle_names_a_rule() {
+        let dir = scratch_dir("universe");
+        std::fs::create_dir_all(&dir).unwrap();
+        let board = dir.join("board.txt");
+        std::fs::write(&board, blinker(6, 6).to_string()).unwrap();
+        let rle = dir.join("seeds.rle");
+        std::fs::write(&rle, "x = 2, y = 1, rule = B2/S\n2o!\n").unwrap();
+
+        let mut grid = Grid::with_boundary(8, 8, BoundaryMode::Wrap);
+        grid.set_rule(RulePreset::HighLife.rule());
+        let mut app = App {
+            grid,
+            ..App::default()
+        };
+        command(&mut app, "universe");
+        assert_eq!(app.status, "universe = B36/S23, torus, Moore");
+
+        command(&mut app, &format!("e {}", board.display()));
+        assert_eq!(app.grid.population(), 3);
+        command(&mut app, "universe");
+        assert_eq!(app.status, "universe = B36/S23, torus, Moore");
+
+        command(&mut app, &format!("e {}", rle.display()));
+        command(&mut app, "universe");
+        assert_eq!(app.status, "universe = B2/S, torus, Moore");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// The top line of the frame, where the title is drawn.
+    fn title_row(app: &App) -> String {
+        let area = Rect::new(0, 0, 120, 30);
+        let mut buf = Buffer::empty(area);
+        app.render(area, &mut buf);
+        (0..area.width).map(|x| buf[(x, 0)].symbol()).collect()
+    }
+
+    #[test]
+    fn shift_w_toggles_wrapping_without_touching_the_cells() {
+        let mut app = App {
+            grid: Grid::new(6, 6),
+            mode: Mode::RUNNING,
+            ..App::default()
+        };
+        app.grid.set_alive(&[(0, 5), (1, 5), (2, 5)]);
+        assert!(title_row(&app).contains("[bounded]"));
+
+        press(&mut app, "W");
+        assert_eq!(app.grid.boundary_mode(), BoundaryMode::Wrap);
+        assert!(title_row(&app).contains("[torus]"));
+        assert_eq!(app.grid.population(), 3);
+        // The blinker on the right edge now has neighbors in the left column
+        app.tick();
+        assert_eq!(
+            app.grid.live_cells().collect::<Vec<_>>(),
+            [(1, 0), (1, 4), (1, 5)]
+        );
+
+        press(&mut app, "W");
+        assert_eq!(app.grid.boundary_mode(), BoundaryMode::Dead);
+        assert!(app.mode == Mode::RUNNING);
+    }
+
+    #[test]
+    fn run_computes_generations_in_chunks_until_done_or_esc() {
+        let mut app = App {
+            grid: blinker(5, 5),
+            ..App::default()
+        };
+        command(&mut app, "run 1001");
+        assert!(app.fast_forward.is_some());
+        // Edits are shut out until it's done
+        press(&mut app, "r");
+        while app.fast_forward.is_some() {
+            app.run_chunk();
+        }
+        assert_eq!(app.status, "Ran 1001 generations");
+        assert_eq!(app.grid.generation(), 1001);
+        assert_eq!(app.grid.population(), 3);
+        // The whole run undoes at once, back to the horizontal blinker
+        press(&mut app, "u");
+        assert_eq!(app.grid.to_string(), blinker(5, 5).to_string());
+
+        // A glider on a torus never settles, so this is still going after a chunk
+        let mut app = App {
+            grid: Grid::with_boundary(64, 64, BoundaryMode::Wrap),
+            ..App::default()
+        };
+        app.grid.stamp_pattern(NamedPattern::Glider, 0, 0);
+        command(&mut app, "run 1000000000");
+        app.run_chunk();
+        let done = app.fast_forward.as_ref().unwrap().done;
+        assert!(done > 0 && done < 1_000_000_000);
+        app.handle_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(app.fast_forward.is_none());
+        assert_eq!(
+            app.status,
+            format!("Stopped after {done} of 1000000000 generations")
+        );
+        assert_eq!(app.grid.generation(), done);
+    }
+
+    #[test]
+    fn progress_bar_fills_in_proportion() {
+        assert_eq!(
+            progress_bar(430_000, 1_000_000),
+            "[########............] gen 430000 / 1000000"
+        );
+        assert_eq!(
+            progress_bar(5, 5),
+            format!("[{}] gen 5 / 5", "#".repeat(20))
+        );
+    }
+
+    #[test]
+    fn classify_reports_what_the_board_settled_into() {
+        let mut app = App {
+            grid: blinker(6, 6),
+            ..App::default()
+        };
+        command(&mut app, "classify");
+        assert_eq!(app.status, "Board: chaotic");
+        app.grid.next_generation();
+        app.grid.next_generation();
+        command(&mut app, "classify");
+        assert_eq!(app.status, "Board: period-2 oscillator");
+        command(&mut app, "classify now");
+        assert_eq!(app.status, ":classify takes no arguments");
+    }
+
+    #[test]
+    fn soup_search_flags_need_each_other() {
+        let options = parse_args(&["--soup-search", "--seeds=5..9", "--generations", "50"])
+            .unwrap()
+            .unwrap();
+        let search = options.soup_search.unwrap();
+        assert_eq!((search.seeds, search.generations), (5..9, 50));
+
+        let search = parse_args(&["--soup-search"]).unwrap().unwrap().soup_search;
+        assert_eq!(search.map(|search| search.seeds), Some(0..100));
+        assert!(parse_args(&[]).unwrap().unwrap().soup_search.is_none());
+
+        assert_eq!(
+            parse_args(&["--seeds", "0..10"]).err().as_deref(),
+            Some("--seeds and --generations only apply to --soup-search")
+        );
+        assert!(parse_args(&["--soup-search", "--seeds", "10..10"]).is_err());
+        assert!(parse_args(&["--soup-search", "--seeds", "10"]).is_err());
+        assert!(parse_args(&["--soup-search", "--generations", "-1"]).is_err());
+        assert!(parse_args(&["--soup-search", "glider.rle"]).is_err());
+        assert!(parse_args(&["--soup-search", "--restore"]).is_err());
+    }
+
+    #[test]
+    fn soup_search_tallies_the_same_way_every_time() {
+        let options = parse_args(&["--soup-search", "--seeds=0..20", "--generations=300"])
+            .unwrap()
+            .unwrap();
+        let options = Options {
+            width: Some(16),
+            height: Some(16),
+            ..options
+        };
+        let search = options.soup_search.as_ref().unwrap();
+        let table = search.run(&options).unwrap();
+        assert_eq!(table, search.run(&options).unwrap());
+
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next(),
+            Some("Soup search: seeds 0..20, 300 generations each on a 16x16 board")
+        );
+        assert_eq!(lines.next_back(), Some("total                      20"));
+        // Every soup is counted once: a count, then its share
+        let counted: usize = lines
+            .map(|line| line.split_whitespace().rev().nth(1).unwrap())
+            .map(|count| count.parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(counted, 20);
+    }
+
+    #[test]
+    fn yanked_region_tiles_the_selection() {
+        let mut app = App {
+            grid: Grid::new(8, 8),
+            ..App::default()
+        };
+        // Nothing to tile with until something's yanked
+        press(&mut app, "vlllt");
+        assert_eq!(app.status, "Nothing yanked to tile with");
+        assert!(app.mode == Mode::NORMAL);
+
+        app.grid.set_alive(&[(0, 0)]);
+        app.cursor_pos = (0, 0);
+        press(&mut app, "vly");
+        app.cursor_pos = (1, 0);
+        press(&mut app, "vlllllt");
+        let tiled = app.grid.live_cells().collect::<Vec<_>>();
+        assert_eq!(tiled, vec![(0, 0), (1, 0), (1, 2), (1, 4)]);
+        press(&mut app, "u");
+        assert_eq!(app.grid.population(), 1);
+    }
+
+    /// An R-pentomino, which grows quickly from its 5 cells.
+    fn r_pentomino() -> Grid {
+        let mut grid = Grid::new(40, 40);
+        grid.load_pattern(".##\n##.\n.#.", 18, 18);
+        grid
+    }
+
+    /// The first generation of the R-pentomino with a population over `threshold`.
+    fn first_over(threshold: usize) -> (u64, usize) {
+        let mut grid = r_pentomino();
+        while grid.population() <= threshold {
+            grid.next_generation();
+        }
+        (grid.generation(), grid.population())
+    }
+
+    #[test]
+    fn watch_reports_when_the_population_first_passes_a_threshold() {
+        let mut app = App {
+            grid: r_pentomino(),
+            ..App::default()
+        };
+        command(&mut app, "watch");
+        assert_eq!(app.status, "Not watching anything");
+        command(&mut app, "watch pop > 20 pause");
+        assert_eq!(app.status, "Watching for pop > 20 pause");
+        let status = app.status_line().to_string();
+        assert!(status.contains("[WATCH pop > 20 pause]"), "{status}");
+
+        app.mode = Mode::RUNNING;
+        while app.mode == Mode::RUNNING {
+            app.tick();
+        }
+        let (generation, population) = first_over(20);
+        assert_eq!(app.grid.generation(), generation);
+        assert_eq!(
+            app.status,
+            format!("Population first went over 20 at gen {generation} ({population})")
+        );
+        // Only the first crossing is reported
+        assert_eq!(app.watch, None);
+
+        // Without `pause`, a `:run` carries on to the end and reports it there
+        let mut app = App {
+            grid: r_pentomino(),
+            ..App::default()
+        };
+        command(&mut app, "watch pop > 50");
+        command(&mut app, "run 60");
+        while app.fast_forward.is_some() {
+            app.run_chunk();
+        }
+        let (generation, population) = first_over(50);
+        assert_eq!(
+            app.status,
+            format!(
+                "Ran 60 generations. Population first went over 50 at gen {generation} ({population})"
+            )
+        );
+
+        command(&mut app, "watch pop < 10");
+        command(&mut app, "watch off");
+        assert_eq!(app.watch, None);
+        command(&mut app, "watch pop = 10");
+        assert_eq!(app.status, "Not > or <: =");
+        command(&mut app, "watch pop > lots");
+        assert_eq!(app.status, "Not a population: lots");
+        command(&mut app, "watch pop > 10 now");
+        assert_eq!(app.status, "Expected `pause`, not now");
+        command(&mut app, "watch cells > 10");
+        assert_eq!(app.status, "Usage: :watch pop >|< <n> [pause], or off");
+    }
+
+    #[test]
+    fn expanding_board_grows_ahead_of_a_glider() {
+        let mut app = App {
+            grid: Grid::new(10, 10),
+            cursor_pos: (2, 2),
+            ..App::default()
+        };
+        app.grid.load_pattern(NamedPattern::Glider.cells(), 5, 5);
+        command(&mut app, "set expand on");
+        assert_eq!(app.status, "expand = on");
+
+        // Left bounded, a glider this close to the corner would crash into it
+        app.mode = Mode::RUNNING;
+        for _ in 0..40 {
+            app.tick();
+        }
+        assert_eq!(app.grid.population(), 5);
+        assert_eq!(
+            (app.grid.width, app.grid.height),
+            (10 + EXPAND_STEP, 10 + EXPAND_STEP)
+        );
+        // 40 generations carry it 10 cells down and to the right
+        let (_, max_r, _, max_c) = app.grid.live_bounds().unwrap();
+        assert_eq!((max_r, max_c), (7 + 10, 7 + 10));
+        // Growing to the right and below leaves the cursor on the same cell
+        assert_eq!(app.cursor_pos, (2, 2));
+
+        // Growing at the top or left moves everything, cursor and view included
+        let mut app = App {
+            grid: Grid::new(10, 10),
+            cursor_pos: (5, 5),
+            scroll_row: 1,
+            ..App::default()
+        };
+        app.grid.load_pattern("##\n##", 0, 4);
+        command(&mut app, "set expand on");
+        press(&mut app, "n");
+        assert_eq!(
+            app.grid.live_bounds(),
+            Some((EXPAND_STEP, EXPAND_STEP + 1, 4, 5))
+        );
+        assert_eq!(app.cursor_pos, (5 + EXPAND_STEP, 5));
+        assert_eq!(app.scroll_row, 1 + EXPAND_STEP);
+
+        // Up against the size cap it stops expanding instead
+        let mut app = App {
+            grid: Grid::new(MAX_EXPANDED_SIDE, 6),
+            ..App::default()
+        };
+        app.grid.load_pattern("##\n##", 2, 0);
+        command(&mut app, "set expand on");
+        press(&mut app, "n");
+        assert_eq!(app.grid.width, MAX_EXPANDED_SIDE);
+        assert!(!app.auto_expand);
+        assert_eq!(
+            app.status,
+            "The board can't grow past 4096x4096, stopped expanding"
+        );
+    }
+
+    #[test]
+    fn backend_follows_density_until_pinned() {
+        // Blocks are still lifes, so each one holds four cells on a 100x100 board
+        fn place_blocks(app: &mut App, blocks: std::ops::Range<usize>) {
+            for i in blocks {
+                let (row, col) = (4 * (i / 25), 4 * (i % 25));
+                app.grid
+                    .fill_region(row, row + 1, col, col + 1, CellState::Alive);
+            }
+        }
+        let mut app = App {
+            grid: Grid::new(100, 100),
+            ..App::default()
+        };
+        command(&mut app, "backend");
+        assert_eq!(app.status, "backend = dense (auto)");
+
+        place_blocks(&mut app, 0..20);
+        press(&mut app, "n");
+        assert_eq!(app.grid.backend(), Backend::Sparse);
+        assert!(app.status_line().to_string().contains("[SPARSE]"));
+        // Between the two thresholds neither backend is left
+        place_blocks(&mut app, 20..50);
+        press(&mut app, "n");
+        assert_eq!(app.grid.backend(), Backend::Sparse);
+        place_blocks(&mut app, 50..80);
+        press(&mut app, "n");
+        assert_eq!(app.grid.backend(), Backend::Dense);
+        assert!(!app.status_line().to_string().contains("[SPARSE]"));
+
+        command(&mut app, "backend sparse");
+        press(&mut app, "n");
+        assert_eq!(app.grid.backend(), Backend::Sparse);
+        command(&mut app, "backend");
+        assert_eq!(app.status, "backend = sparse");
+        command(&mut app, "backend auto");
+        press(&mut app, "n");
+        assert_eq!(app.grid.backend(), Backend::Dense);
+        command(&mut app, "backend turbo");
+        assert_eq!(app.status, "Usage: :backend [auto|dense|sparse]");
     }
 }
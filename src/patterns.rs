@@ -0,0 +1,200 @@
+//! A small library of well-known patterns that can be stamped onto a grid by name.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+/// A built-in pattern, drawn in the `#` / `.` format read by `Grid::load_pattern`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamedPattern {
+    Glider,
+    Lwss,
+    Blinker,
+    Toad,
+    Beacon,
+    Pulsar,
+    GosperGliderGun,
+}
+
+impl NamedPattern {
+    /// Every built-in pattern, smallest first.
+    pub const ALL: [NamedPattern; 7] = [
+        NamedPattern::Blinker,
+        NamedPattern::Glider,
+        NamedPattern::Toad,
+        NamedPattern::Beacon,
+        NamedPattern::Lwss,
+        NamedPattern::Pulsar,
+        NamedPattern::GosperGliderGun,
+    ];
+
+    /// The pattern's lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedPattern::Glider => "glider",
+            NamedPattern::Lwss => "lwss",
+            NamedPattern::Blinker => "blinker",
+            NamedPattern::Toad => "toad",
+            NamedPattern::Beacon => "beacon",
+            NamedPattern::Pulsar => "pulsar",
+            NamedPattern::GosperGliderGun => "gosper",
+        }
+    }
+
+    /// The pattern's cells, one line per row with `#` for live cells.
+    pub fn cells(self) -> &'static str {
+        match self {
+            // Travels down and to the right
+            NamedPattern::Glider => ".#.\n..#\n###",
+            // Lightweight spaceship, travels left
+            NamedPattern::Lwss => ".#..#\n#....\n#...#\n####.",
+            NamedPattern::Blinker => "###",
+            NamedPattern::Toad => ".###\n###.",
+            NamedPattern::Beacon => "##..\n##..\n..##\n..##",
+            NamedPattern::Pulsar => concat!(
+                "..###...###..\n",
+                ".............\n",
+                "#....#.#....#\n",
+                "#....#.#....#\n",
+                "#....#.#....#\n",
+                "..###...###..\n",
+                ".............\n",
+                "..###...###..\n",
+                "#....#.#....#\n",
+                "#....#.#....#\n",
+                "#....#.#....#\n",
+                ".............\n",
+                "..###...###..",
+            ),
+            // Fires a glider down and to the right every 30 generations
+            NamedPattern::GosperGliderGun => concat!(
+                "........................#...........\n",
+                "......................#.#...........\n",
+                "............##......##............##\n",
+                "...........#...#....##............##\n",
+                "##........#.....#...##..............\n",
+                "##........#...#.##....#.#...........\n",
+                "..........#.....#.......#...........\n",
+                "...........#...#....................\n",
+                "............##......................",
+            ),
+        }
+    }
+
+    /// The next pattern in `ALL`, wrapping around after the last.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for NamedPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returned by `NamedPattern::from_str` for a name that isn't in the library.
+#[derive(Debug, PartialEq)]
+pub struct UnknownPattern(pub String);
+
+impl fmt::Display for UnknownPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no built-in pattern named `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPattern {}
+
+impl FromStr for NamedPattern {
+    type Err = UnknownPattern;
+
+    /// Looks a pattern up by its `name`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NamedPattern::ALL
+            .into_iter()
+            .find(|pattern| pattern.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownPattern(s.to_string()))
+    }
+}
+
+impl Grid {
+    /// Sets the live cells of `pattern` with its top-left corner at (row, col),
+    /// leaving the cells around them untouched. Anything that would land outside
+    /// the grid is clipped.
+    pub fn stamp_pattern(&mut self, pattern: NamedPattern, row: usize, col: usize) {
+        self.load_pattern(pattern.cells(), row, col);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellState;
+
+    #[test]
+    fn names_round_trip_through_from_str() {
+        for pattern in NamedPattern::ALL {
+            assert_eq!(pattern.name().parse(), Ok(pattern));
+            assert_eq!(pattern.to_string().to_uppercase().parse(), Ok(pattern));
+        }
+        assert_eq!(
+            "spaceship".parse::<NamedPattern>(),
+            Err(UnknownPattern("spaceship".to_string()))
+        );
+    }
+
+    #[test]
+    fn next_cycles_through_every_pattern() {
+        let mut pattern = NamedPattern::ALL[0];
+        for expected in NamedPattern::ALL.iter().cycle().skip(1).take(7) {
+            pattern = pattern.next();
+            assert_eq!(pattern, *expected);
+        }
+        assert_eq!(pattern, NamedPattern::ALL[0]);
+    }
+
+    #[test]
+    fn stamps_keep_the_cells_around_them() {
+        let mut grid = Grid::new(8, 8);
+        grid.set_alive(&[(0, 0), (7, 7)]);
+        grid.stamp_pattern(NamedPattern::Beacon, 2, 2);
+        assert_eq!(grid.population(), 2 + 8);
+        assert_eq!(grid.live_bounds(), Some((0, 7, 0, 7)));
+    }
+
+    #[test]
+    fn stamps_are_clipped_at_the_edges() {
+        // The glider's bottom row falls off the board
+        let mut grid = Grid::new(5, 5);
+        grid.stamp_pattern(NamedPattern::Glider, 3, 2);
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), vec![(3, 3), (4, 4)]);
+        // Only its left column fits, holding one live cell
+        grid.stamp_pattern(NamedPattern::Glider, 0, 4);
+        assert_eq!(grid.population(), 3);
+        assert_eq!(grid.get_copy(2, 4), Some(CellState::Alive));
+    }
+
+    #[test]
+    fn spaceships_reappear_shifted() {
+        // (pattern, generations per period, rows and columns moved per period)
+        let cases = [
+            (NamedPattern::Glider, 4, (1, 1)),
+            (NamedPattern::Lwss, 4, (0, -2)),
+        ];
+        for (pattern, period, (d_row, d_col)) in cases {
+            let mut grid = Grid::new(20, 20);
+            grid.stamp_pattern(pattern, 8, 8);
+            let start: Vec<(usize, usize)> = grid.live_cells().collect();
+            for _ in 0..period {
+                grid.next_generation();
+            }
+            let moved: Vec<(usize, usize)> = start
+                .iter()
+                .map(|&(r, c)| (r.wrapping_add_signed(d_row), c.wrapping_add_signed(d_col)))
+                .collect();
+            assert_eq!(grid.live_cells().collect::<Vec<_>>(), moved, "{pattern}");
+        }
+    }
+}
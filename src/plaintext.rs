@@ -0,0 +1,182 @@
+//! Reading and writing patterns in the plaintext `.cells` format used by the LifeWiki.
+//!
+//! Each line is one row of the pattern, with `O` for a live cell and `.` for a
+//! dead one. Lines starting with `!` are comments (usually a name and description).
+
+use std::fmt;
+
+use crate::grid::{CellState, Grid, GridError};
+
+/// Errors returned when a plaintext pattern, or the `Display` text parsed by
+/// `Grid::from_str`, can't be turned into a `Grid`.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// A character other than a live or dead cell, at a 1-based line of the input.
+    UnexpectedChar { line: usize, ch: char },
+    /// A row with a different number of cells than the first, where rows must match.
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The grid for the pattern couldn't be created.
+    Grid(GridError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar { line, ch } => {
+                write!(f, "unexpected character `{ch}` on line {line}")
+            }
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line} has {found} cells, expected {expected}"),
+            ParseError::Grid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Grid(err) => Some(err),
+            ParseError::UnexpectedChar { .. } | ParseError::RaggedRow { .. } => None,
+        }
+    }
+}
+
+impl From<GridError> for ParseError {
+    fn from(err: GridError) -> Self {
+        ParseError::Grid(err)
+    }
+}
+
+impl Grid {
+    /// Parses a plaintext (`.cells`) pattern into a grid as wide as its longest line
+    /// and as tall as its number of lines.
+    ///
+    /// `O`, `o` and `*` are live cells; `.` and spaces are dead. `!` comment lines
+    /// are skipped, and blank lines count as empty rows except at the very end.
+    pub fn from_plaintext(text: &str) -> Result<Grid, ParseError> {
+        let mut rows: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.starts_with('!'))
+            .map(|(i, line)| (i + 1, line.trim_end()))
+            .collect();
+        while rows.last().is_some_and(|(_, line)| line.is_empty()) {
+            rows.pop();
+        }
+
+        let width = rows
+            .iter()
+            .map(|(_, line)| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut grid = Grid::try_new(width, rows.len())?;
+
+        for (r, &(line_number, line)) in rows.iter().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                match ch {
+                    'O' | 'o' | '*' => grid.set(r, c, CellState::Alive),
+                    '.' | ' ' => {}
+                    ch => {
+                        return Err(ParseError::UnexpectedChar {
+                            line: line_number,
+                            ch,
+                        })
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Encodes the live cells as a plaintext pattern, trimmed to their bounding box,
+    /// that `from_plaintext` reads back as the same pattern. An empty board encodes
+    /// as an empty string.
+    pub fn to_plaintext(&self) -> String {
+        let Some((min_r, max_r, min_c, max_c)) = self.live_bounds() else {
+            return String::new();
+        };
+
+        let mut text = String::new();
+        for r in min_r..=max_r {
+            for c in min_c..=max_c {
+                text.push(match self.get_copy(r, c) {
+                    Some(CellState::Alive) => 'O',
+                    _ => '.',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::NamedPattern;
+
+    const GLIDER: &str = "!Name: Glider\n!\n.O.\n..O\nOOO\n";
+
+    fn live(grid: &Grid) -> Vec<(usize, usize)> {
+        grid.live_cells().collect()
+    }
+
+    #[test]
+    fn parses_a_block_under_a_comment_header() {
+        let grid = Grid::from_plaintext(GLIDER).unwrap();
+        assert_eq!((grid.width, grid.height), (3, 3));
+        assert_eq!(live(&grid), [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn the_longest_line_sets_the_width() {
+        let grid = Grid::from_plaintext("O\n...O.\n.O").unwrap();
+        assert_eq!((grid.width, grid.height), (5, 3));
+        assert_eq!(live(&grid), [(0, 0), (1, 3), (2, 1)]);
+    }
+
+    #[test]
+    fn only_trailing_blank_lines_are_dropped() {
+        let grid = Grid::from_plaintext("O\n\nO\n\n\n").unwrap();
+        assert_eq!((grid.width, grid.height), (1, 3));
+        assert_eq!(live(&grid), [(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn lowercase_o_and_asterisks_are_live() {
+        let grid = Grid::from_plaintext("o.*\n*O.").unwrap();
+        assert_eq!(live(&grid), [(0, 0), (0, 2), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn unexpected_chars_report_the_line_counting_comments() {
+        assert_eq!(
+            Grid::from_plaintext("!Name: Typo\n!\n.O.\n.X.").unwrap_err(),
+            ParseError::UnexpectedChar { line: 4, ch: 'X' }
+        );
+    }
+
+    #[test]
+    fn round_trips_through_to_plaintext() {
+        let mut grid = Grid::new(12, 10);
+        grid.stamp_pattern(NamedPattern::Glider, 4, 5);
+        grid.stamp_pattern(NamedPattern::Blinker, 1, 1);
+
+        let parsed = Grid::from_plaintext(&grid.to_plaintext()).unwrap();
+        // Trimmed to the bounding box, so the cells come back shifted by its corner
+        let (min_r, _, min_c, _) = grid.live_bounds().unwrap();
+        let shifted: Vec<_> = grid
+            .live_cells()
+            .map(|(r, c)| (r - min_r, c - min_c))
+            .collect();
+        assert_eq!(live(&parsed), shifted);
+        assert_eq!(Grid::new(4, 4).to_plaintext(), "");
+    }
+}
@@ -0,0 +1,340 @@
+//! Reading and writing patterns in the Run Length Encoded (RLE) format used by Golly
+//! and the LifeWiki.
+//!
+//! An RLE file is an optional block of `#` comment lines, a header such as
+//! `x = 3, y = 3, rule = B3/S23`, and a body of run-length tokens: `b` (dead),
+//! `o` (alive) and `$` (end of row), each optionally prefixed by a repeat count,
+//! with `!` marking the end of the pattern.
+
+use std::fmt;
+
+use crate::grid::{CellState, Grid, GridError};
+use crate::rule::{LifelikeRule, RuleError};
+
+/// Errors returned when an RLE string can't be turned into a `Grid`.
+#[derive(Debug, PartialEq)]
+pub enum RleError {
+    /// The input has no `x = .., y = ..` header line.
+    MissingHeader,
+    /// The header line is present but its `x` or `y` is missing or not a number.
+    InvalidHeader(String),
+    /// A character in the body that isn't a run count, `b`, `o`, `$` or `!`.
+    UnexpectedChar(char),
+    /// A run count too large to represent.
+    InvalidRunCount,
+    /// The header's `rule` isn't a Life-like rulestring such as `B3/S23`.
+    Rule(RuleError),
+    /// A live cell at (row, col) lies outside the size declared in the header.
+    OutOfBounds { row: usize, col: usize },
+    /// The grid for the pattern couldn't be created.
+    Grid(GridError),
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RleError::MissingHeader => write!(f, "missing `x = .., y = ..` header line"),
+            RleError::InvalidHeader(line) => write!(f, "invalid RLE header `{line}`"),
+            RleError::UnexpectedChar(ch) => write!(f, "unexpected character `{ch}` in RLE body"),
+            RleError::InvalidRunCount => write!(f, "run count is too large"),
+            RleError::Rule(err) => write!(f, "{err}"),
+            RleError::OutOfBounds { row, col } => write!(
+                f,
+                "live cell at row {row}, column {col} is outside the size in the header"
+            ),
+            RleError::Grid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RleError::Grid(err) => Some(err),
+            RleError::Rule(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<GridError> for RleError {
+    fn from(err: GridError) -> Self {
+        RleError::Grid(err)
+    }
+}
+
+impl From<RuleError> for RleError {
+    fn from(err: RuleError) -> Self {
+        RleError::Rule(err)
+    }
+}
+
+/// A decoded RLE pattern: its declared size and rule, and the (row, col) of every
+/// live cell.
+struct RlePattern {
+    width: usize,
+    height: usize,
+    rule: Option<LifelikeRule>,
+    alive: Vec<(usize, usize)>,
+}
+
+impl Grid {
+    /// Parses an RLE pattern into a grid sized exactly to the `x`/`y` in its header.
+    ///
+    /// A `rule` in the header (a Life-like rulestring such as `B36/S23`) becomes the
+    /// grid's rule; without one the grid runs the default B3/S23.
+    pub fn from_rle(rle: &str) -> Result<Grid, RleError> {
+        let pattern = parse_rle(rle)?;
+        let mut grid = Grid::try_new(pattern.width, pattern.height)?;
+        if let Some(rule) = pattern.rule {
+            grid.set_rule(rule);
+        }
+        grid.set_alive(&pattern.alive);
+        Ok(grid)
+    }
+
+    /// Encodes the live cells as an RLE string, trimmed to their bounding box, that
+    /// `from_rle` (or Golly) reads back as the same pattern under the same rule. An
+    /// empty board encodes as a 0 x 0 pattern.
+    pub fn to_rle(&self) -> String {
+        let rule = LifelikeRule::from_rule(self.rule());
+        let Some((min_r, max_r, min_c, max_c)) = self.live_bounds() else {
+            return format!("x = 0, y = 0, rule = {rule}\n!\n");
+        };
+
+        let mut tokens = Vec::new();
+        let mut last_row = None;
+        for r in min_r..=max_r {
+            let mut runs: Vec<(usize, char)> = Vec::new();
+            for c in min_c..=max_c {
+                let tag = match self.get_copy(r, c) {
+                    Some(CellState::Alive) => 'o',
+                    _ => 'b',
+                };
+                match runs.last_mut() {
+                    Some((run, last_tag)) if *last_tag == tag => *run += 1,
+                    _ => runs.push((1, tag)),
+                }
+            }
+            // Dead cells at the end of a row are implied by the row break
+            if runs.last().is_some_and(|&(_, tag)| tag == 'b') {
+                runs.pop();
+            }
+            if runs.is_empty() {
+                continue;
+            }
+            // Blank rows in between collapse into a single counted `$`
+            if let Some(last_row) = last_row {
+                tokens.push(encode_run(r - last_row, '$'));
+            }
+            tokens.extend(runs.into_iter().map(|(run, tag)| encode_run(run, tag)));
+            last_row = Some(r);
+        }
+        tokens.push("!".to_string());
+
+        let mut rle = format!(
+            "x = {}, y = {}, rule = {rule}\n",
+            max_c - min_c + 1,
+            max_r - min_r + 1
+        );
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len + token.len() > MAX_LINE_LEN {
+                rle.push('\n');
+                line_len = 0;
+            }
+            line_len += token.len();
+            rle.push_str(&token);
+        }
+        rle.push('\n');
+        rle
+    }
+}
+
+// The RLE format asks for body lines of at most 70 characters.
+const MAX_LINE_LEN: usize = 70;
+
+/// Formats one RLE token, leaving out the count for a run of 1.
+fn encode_run(run: usize, tag: char) -> String {
+    if run == 1 {
+        tag.to_string()
+    } else {
+        format!("{run}{tag}")
+    }
+}
+
+/// Decodes the header and body of an RLE string, skipping blank and `#` comment lines.
+fn parse_rle(rle: &str) -> Result<RlePattern, RleError> {
+    let mut lines = rle
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let header = lines.next().ok_or(RleError::MissingHeader)?;
+    let (width, height, rule) = parse_header(header)?;
+
+    let mut alive = Vec::new();
+    let (mut row, mut col) = (0usize, 0usize);
+    let mut count: Option<usize> = None;
+
+    'body: for line in lines {
+        for ch in line.chars() {
+            match ch {
+                '0'..='9' => {
+                    let digit = ch as usize - '0' as usize;
+                    let run = count
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(digit))
+                        .ok_or(RleError::InvalidRunCount)?;
+                    count = Some(run);
+                }
+                'b' => col = col.saturating_add(count.take().unwrap_or(1)),
+                'o' => {
+                    let run = count.take().unwrap_or(1);
+                    let end = col.saturating_add(run);
+                    // Check the whole run up front so a huge count can't loop for long
+                    if row >= height || end > width {
+                        let col = if row >= height { col } else { col.max(width) };
+                        return Err(RleError::OutOfBounds { row, col });
+                    }
+                    alive.extend((col..end).map(|c| (row, c)));
+                    col = end;
+                }
+                '$' => {
+                    row = row.saturating_add(count.take().unwrap_or(1));
+                    col = 0;
+                }
+                '!' => break 'body,
+                ch if ch.is_whitespace() => {}
+                other => return Err(RleError::UnexpectedChar(other)),
+            }
+        }
+    }
+
+    Ok(RlePattern {
+        width,
+        height,
+        rule,
+        alive,
+    })
+}
+
+/// Reads `x`, `y` and the optional `rule` from a header like
+/// `x = 3, y = 3, rule = B3/S23`.
+fn parse_header(header: &str) -> Result<(usize, usize, Option<LifelikeRule>), RleError> {
+    let invalid = || RleError::InvalidHeader(header.to_string());
+    let (mut width, mut height, mut rule) = (None, None, None);
+
+    for field in header.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = Some(value.parse().map_err(|_| invalid())?),
+            "y" => height = Some(value.parse().map_err(|_| invalid())?),
+            "rule" => rule = Some(value.parse()?),
+            // Any other keys don't affect the pattern
+            _ => {}
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::NamedPattern;
+
+    const GLIDER: &str = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+
+    const GOSPER_GLIDER_GUN: &str = "\
+#N Gosper glider gun
+#C The first known gun and the first known finite pattern with unbounded growth.
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!
+";
+
+    fn stamped(pattern: NamedPattern, width: usize, height: usize) -> Grid {
+        let mut grid = Grid::new(width, height);
+        grid.stamp_pattern(pattern, 0, 0);
+        grid
+    }
+
+    #[test]
+    fn parses_the_glider() {
+        let grid = Grid::from_rle(GLIDER).unwrap();
+        assert_eq!((grid.width, grid.height), (3, 3));
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid, stamped(NamedPattern::Glider, 3, 3));
+    }
+
+    #[test]
+    fn parses_the_gosper_glider_gun() {
+        let grid = Grid::from_rle(GOSPER_GLIDER_GUN).unwrap();
+        assert_eq!((grid.width, grid.height), (36, 9));
+        assert_eq!(grid.population(), 36);
+        assert_eq!(grid, stamped(NamedPattern::GosperGliderGun, 36, 9));
+    }
+
+    #[test]
+    fn round_trips_through_to_rle() {
+        let mut grid = Grid::new(40, 20);
+        grid.stamp_pattern(NamedPattern::GosperGliderGun, 3, 2);
+        grid.stamp_pattern(NamedPattern::Blinker, 17, 30);
+
+        let parsed = Grid::from_rle(&grid.to_rle()).unwrap();
+        // Trimmed to the bounding box, so the cells come back shifted by its corner
+        let (min_r, _, min_c, _) = grid.live_bounds().unwrap();
+        let shifted: Vec<_> = grid
+            .live_cells()
+            .map(|(r, c)| (r - min_r, c - min_c))
+            .collect();
+        assert_eq!(parsed.live_cells().collect::<Vec<_>>(), shifted);
+    }
+
+    #[test]
+    fn header_rule_is_read_and_written() {
+        let grid = Grid::from_rle("x = 3, y = 1, rule = b36/s23\n3o!").unwrap();
+        assert_eq!(LifelikeRule::from_rule(grid.rule()).to_string(), "B36/S23");
+        assert!(grid.to_rle().starts_with("x = 3, y = 1, rule = B36/S23\n"));
+
+        let conway = Grid::from_rle(GLIDER).unwrap();
+        assert!(conway.to_rle().contains("rule = B3/S23"));
+        assert_eq!(Grid::new(2, 2).to_rle(), "x = 0, y = 0, rule = B3/S23\n!\n");
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(
+            Grid::from_rle("#C only a comment\n"),
+            Err(RleError::MissingHeader)
+        );
+        assert!(matches!(
+            Grid::from_rle("x = three, y = 3\nbob!"),
+            Err(RleError::InvalidHeader(_))
+        ));
+        assert_eq!(
+            Grid::from_rle("x = 3, y = 3\nbqb!"),
+            Err(RleError::UnexpectedChar('q'))
+        );
+        assert_eq!(
+            Grid::from_rle("x = 2, y = 1\n3o!"),
+            Err(RleError::OutOfBounds { row: 0, col: 2 })
+        );
+        assert_eq!(
+            Grid::from_rle("x = 2, y = 1\n99999999999999999999999o!"),
+            Err(RleError::InvalidRunCount)
+        );
+        assert_eq!(
+            Grid::from_rle("x = 3, y = 1, rule = Wireworld\n3o!"),
+            Err(RleError::Rule(RuleError::Malformed(
+                "Wireworld".to_string()
+            )))
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! The rules deciding how cells are born, survive and die each generation.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::CellState;
+
+/// A cellular automaton rule for a two-state board.
+///
+/// `Grid::next_generation` calls `next_state` once per cell, so implementations
+/// should be cheap: a `match` or table lookup rather than anything allocating.
+/// Rules must be `Send + Sync` so grids can be shared with (and computed on)
+/// other threads.
+pub trait Rule: Send + Sync {
+    /// The state of a cell in the next generation, given its current state and how
+    /// many of its neighbors are alive: 0 to 8 with the Moore neighborhood, or 0 to 4
+    /// with von Neumann.
+    fn next_state(&self, current: CellState, live_neighbors: usize) -> CellState;
+}
+
+/// Conway's Game of Life (B3/S23): a dead cell with exactly 3 live neighbors is
+/// born, and a live cell with 2 or 3 live neighbors survives.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConwayRule;
+
+impl Rule for ConwayRule {
+    fn next_state(&self, current: CellState, live_neighbors: usize) -> CellState {
+        match current {
+            // Rule 1: Any live cell with 2 or 3 live neighbors lives.
+            // Rule 2: Any live cell with <2 or >3 neighbors dies.
+            CellState::Alive => match live_neighbors {
+                2 | 3 => CellState::Alive,
+                _ => CellState::Dead,
+            },
+            // Rule 3: Any dead cell with exactly 3 live neighbors becomes a live cell.
+            CellState::Dead => {
+                if live_neighbors == 3 {
+                    return CellState::Alive;
+                }
+                CellState::Dead
+            }
+        }
+    }
+}
+
+/// Any "Life-like" rule, given by the neighbor counts at which a dead cell is born
+/// and a live cell survives. Counts are looked up in `[bool; 9]` tables, so this is
+/// as cheap per cell as the hardcoded `ConwayRule`.
+///
+/// Parse one from a rulestring in B/S notation, e.g. `"B36/S23".parse()` for
+/// HighLife or `"B2/S"` for Seeds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LifelikeRule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl LifelikeRule {
+    /// A rule where dead cells with a neighbor count in `birth` come alive and live
+    /// cells with a count in `survival` stay alive. Counts above 8 are ignored.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        let table = |counts: &[usize]| {
+            let mut table = [false; 9];
+            for &count in counts.iter().filter(|&&count| count <= 8) {
+                table[count] = true;
+            }
+            table
+        };
+        LifelikeRule {
+            birth: table(birth),
+            survival: table(survival),
+        }
+    }
+
+    /// The Life-like rule that behaves exactly like `rule`, found by asking it about
+    /// every neighbor count. Lets any rule be written out as a rulestring.
+    pub fn from_rule(rule: &dyn Rule) -> Self {
+        let table = |current: CellState| {
+            std::array::from_fn(|count| rule.next_state(current, count) == CellState::Alive)
+        };
+        LifelikeRule {
+            birth: table(CellState::Dead),
+            survival: table(CellState::Alive),
+        }
+    }
+}
+
+impl Rule for LifelikeRule {
+    fn next_state(&self, current: CellState, live_neighbors: usize) -> CellState {
+        let table = match current {
+            CellState::Alive => &self.survival,
+            CellState::Dead => &self.birth,
+        };
+        if table.get(live_neighbors).copied().unwrap_or(false) {
+            CellState::Alive
+        } else {
+            CellState::Dead
+        }
+    }
+}
+
+/// Errors returned when a rulestring can't be parsed into a `LifelikeRule`.
+#[derive(Debug, PartialEq)]
+pub enum RuleError {
+    /// The rulestring isn't of the form `B<digits>/S<digits>` (or `S../B..`, or
+    /// the bare `<survival>/<birth>` form).
+    Malformed(String),
+    /// A neighbor count outside 0-8.
+    InvalidDigit(char),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Malformed(rule) => {
+                write!(f, "invalid rulestring `{rule}`, expected e.g. `B3/S23`")
+            }
+            RuleError::InvalidDigit(ch) => {
+                write!(
+                    f,
+                    "invalid neighbor count `{ch}` in rulestring (must be 0-8)"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Parses Golly-style rulestrings: `B3/S23`, `S23/B3` (either case), or the older
+/// `23/3` form that lists survival counts first.
+impl FromStr for LifelikeRule {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || RuleError::Malformed(s.to_string());
+        let (first, second) = s.trim().split_once('/').ok_or_else(malformed)?;
+
+        let (birth, survival) = match (first.chars().next(), second.chars().next()) {
+            (Some('B' | 'b'), Some('S' | 's')) => (&first[1..], &second[1..]),
+            (Some('S' | 's'), Some('B' | 'b')) => (&second[1..], &first[1..]),
+            // No letters: survival counts come first
+            _ if !first.starts_with(char::is_alphabetic)
+                && !second.starts_with(char::is_alphabetic) =>
+            {
+                (second, first)
+            }
+            _ => return Err(malformed()),
+        };
+
+        let mut rule = LifelikeRule::new(&[], &[]);
+        for (digits, table) in [(birth, &mut rule.birth), (survival, &mut rule.survival)] {
+            for ch in digits.chars() {
+                match ch.to_digit(10) {
+                    Some(count) if count <= 8 => table[count as usize] = true,
+                    _ => return Err(RuleError::InvalidDigit(ch)),
+                }
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// Writes the rule in canonical `B<digits>/S<digits>` form.
+impl fmt::Display for LifelikeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |table: &[bool; 9]| -> String {
+            (0..9)
+                .filter(|&count| table[count])
+                .map(|count| char::from(b'0' + count as u8))
+                .collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+/// A well-known Life-like rule that can be picked by name instead of rulestring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RulePreset {
+    Conway,
+    HighLife,
+    Seeds,
+    Replicator,
+    LifeWithoutDeath,
+}
+
+impl RulePreset {
+    /// Every preset, in the order they're listed to the user.
+    pub const ALL: [RulePreset; 5] = [
+        RulePreset::Conway,
+        RulePreset::HighLife,
+        RulePreset::Seeds,
+        RulePreset::Replicator,
+        RulePreset::LifeWithoutDeath,
+    ];
+
+    /// The preset's lowercase name, as accepted by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RulePreset::Conway => "conway",
+            RulePreset::HighLife => "highlife",
+            RulePreset::Seeds => "seeds",
+            RulePreset::Replicator => "replicator",
+            RulePreset::LifeWithoutDeath => "lifewithoutdeath",
+        }
+    }
+
+    /// The preset's rulestring in B/S notation.
+    pub fn rulestring(self) -> &'static str {
+        match self {
+            RulePreset::Conway => "B3/S23",
+            // Conway plus births on 6, which gives it a small self-replicating pattern
+            RulePreset::HighLife => "B36/S23",
+            // Nothing survives, so almost everything explodes
+            RulePreset::Seeds => "B2/S",
+            // Every cell takes the parity of its neighbors, so any pattern is copied
+            // eight times over
+            RulePreset::Replicator => "B1357/S1357",
+            // Conway's births, but live cells never die
+            RulePreset::LifeWithoutDeath => "B3/S012345678",
+        }
+    }
+
+    /// The rule itself.
+    pub fn rule(self) -> LifelikeRule {
+        self.rulestring()
+            .parse()
+            .expect("preset rulestrings are well-formed")
+    }
+}
+
+impl fmt::Display for RulePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returned by `RulePreset::from_str` for a name that isn't a preset.
+#[derive(Debug, PartialEq)]
+pub struct UnknownPreset(pub String);
+
+impl fmt::Display for UnknownPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no rule preset named `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPreset {}
+
+impl FromStr for RulePreset {
+    type Err = UnknownPreset;
+
+    /// Looks a preset up by its `name`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RulePreset::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownPreset(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    /// Seeds (B2/S): cells with exactly two live neighbors are born, and none survive.
+    struct Seeds;
+
+    impl Rule for Seeds {
+        fn next_state(&self, _current: CellState, live_neighbors: usize) -> CellState {
+            if live_neighbors == 2 {
+                CellState::Alive
+            } else {
+                CellState::Dead
+            }
+        }
+    }
+
+    #[test]
+    fn conway_rule_is_b3_s23() {
+        for count in 0..=8 {
+            let born = ConwayRule.next_state(CellState::Dead, count);
+            let survives = ConwayRule.next_state(CellState::Alive, count);
+            assert_eq!(born == CellState::Alive, count == 3, "birth on {count}");
+            assert_eq!(
+                survives == CellState::Alive,
+                count == 2 || count == 3,
+                "survival on {count}"
+            );
+        }
+    }
+
+    #[test]
+    fn grids_run_a_custom_rule() {
+        let mut grid = Grid::new(6, 6);
+        grid.set_rule(Seeds);
+        grid.set_alive(&[(2, 2), (2, 3)]);
+        grid.next_generation();
+        assert_eq!(
+            grid.live_cells().collect::<Vec<_>>(),
+            vec![(1, 2), (1, 3), (3, 2), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn b3_s23_matches_conway_on_a_soup() {
+        let mut conway = Grid::new(40, 30);
+        conway.randomize(0.4, 3);
+        let mut parsed = conway.clone();
+        parsed.set_rule("B3/S23".parse::<LifelikeRule>().unwrap());
+        for generation in 1..=30 {
+            conway.next_generation();
+            parsed.next_generation();
+            assert_eq!(parsed, conway, "generation {generation}");
+        }
+    }
+
+    #[test]
+    fn highlife_births_on_six_neighbors() {
+        // (1, 1) is dead with six live neighbors
+        let seed = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0)];
+        let mut conway = Grid::new(5, 5);
+        conway.set_alive(&seed);
+        let mut highlife = conway.clone();
+        highlife.set_rule("B36/S23".parse::<LifelikeRule>().unwrap());
+        conway.next_generation();
+        highlife.next_generation();
+        assert_eq!(conway.get_copy(1, 1), Some(CellState::Dead));
+        assert_eq!(highlife.get_copy(1, 1), Some(CellState::Alive));
+    }
+
+    #[test]
+    fn rulestrings_in_every_form_parse_to_the_same_rule() {
+        let highlife = LifelikeRule::new(&[3, 6], &[2, 3]);
+        for text in ["B36/S23", "b36/s23", "S23/B36", " 23/36 "] {
+            assert_eq!(text.parse(), Ok(highlife), "{text}");
+        }
+        assert_eq!(highlife.to_string(), "B36/S23");
+        assert_eq!("B2/S".parse::<LifelikeRule>().unwrap().to_string(), "B2/S");
+        assert_eq!(LifelikeRule::from_rule(&ConwayRule).to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn malformed_rulestrings_are_rejected() {
+        assert_eq!(
+            "B3S23".parse::<LifelikeRule>(),
+            Err(RuleError::Malformed("B3S23".to_string()))
+        );
+        assert_eq!(
+            "B3/X23".parse::<LifelikeRule>(),
+            Err(RuleError::Malformed("B3/X23".to_string()))
+        );
+        assert_eq!(
+            "B39/S23".parse::<LifelikeRule>(),
+            Err(RuleError::InvalidDigit('9'))
+        );
+        assert_eq!(
+            "B3/S2a".parse::<LifelikeRule>(),
+            Err(RuleError::InvalidDigit('a'))
+        );
+    }
+
+    #[test]
+    fn presets_parse_by_name_to_their_rulestrings() {
+        for preset in RulePreset::ALL {
+            assert_eq!(preset.name().parse::<RulePreset>(), Ok(preset));
+            assert_eq!(preset.rule().to_string(), preset.rulestring());
+        }
+        assert_eq!("HighLife".parse(), Ok(RulePreset::HighLife));
+        assert_eq!(
+            "brain".parse::<RulePreset>(),
+            Err(UnknownPreset("brain".to_string()))
+        );
+        assert_eq!(
+            RulePreset::Conway.rule(),
+            LifelikeRule::from_rule(&ConwayRule)
+        );
+    }
+
+    #[test]
+    fn replicator_copies_a_cell_eight_times() {
+        // Each cell becomes the parity of its eight neighbors, which is linear over XOR,
+        // so after 2^k generations any pattern reappears at the eight offsets of
+        // {-2^k, 0, 2^k} in both directions, leaving the original spot empty
+        let mut grid = Grid::new(21, 21);
+        grid.set_rule(RulePreset::Replicator.rule());
+        grid.set_alive(&[(10, 10)]);
+        for _ in 0..4 {
+            grid.next_generation();
+        }
+        let copies: Vec<_> = [6, 10, 14]
+            .into_iter()
+            .flat_map(|row| [6, 10, 14].map(|col| (row, col)))
+            .filter(|&cell| cell != (10, 10))
+            .collect();
+        assert_eq!(grid.live_cells().collect::<Vec<_>>(), copies);
+    }
+
+    #[test]
+    fn life_without_death_never_kills_a_cell() {
+        let mut grid = Grid::new(30, 30);
+        grid.set_rule(RulePreset::LifeWithoutDeath.rule());
+        grid.randomize(0.2, 7);
+        for _ in 0..20 {
+            let before: Vec<_> = grid.live_cells().collect();
+            grid.next_generation();
+            assert!(before
+                .iter()
+                .all(|&(r, c)| grid.get_copy(r, c) == Some(CellState::Alive)));
+        }
+
+        // A blinker can't oscillate when nothing dies, so it grows instead
+        let mut blinker = Grid::new(9, 9);
+        blinker.set_rule(RulePreset::LifeWithoutDeath.rule());
+        blinker.set_alive(&[(4, 3), (4, 4), (4, 5)]);
+        blinker.next_generation();
+        assert_eq!(blinker.population(), 5);
+    }
+}
@@ -0,0 +1,167 @@
+//! An unbounded Game of Life board that only stores its live cells.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::{CellState, Grid};
+
+/// A board with no edges, backed by the set of live `(row, col)` coordinates.
+///
+/// Memory and `next_generation` time scale with the number of live cells rather
+/// than the area, which suits sparse patterns spread over a huge space (gliders,
+/// guns firing outward). Coordinates may be negative; arithmetic wraps at the
+/// limits of `i64`, which no pattern will reach in practice.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SparseGrid {
+    live: HashSet<(i64, i64)>,
+    generation: u64,
+}
+
+impl SparseGrid {
+    /// An empty board.
+    pub fn new() -> Self {
+        SparseGrid::default()
+    }
+
+    /// Returns the state of the cell at (row, col).
+    pub fn get(&self, row: i64, col: i64) -> CellState {
+        if self.live.contains(&(row, col)) {
+            CellState::Alive
+        } else {
+            CellState::Dead
+        }
+    }
+
+    /// Sets the cell at (row, col) to `state`.
+    pub fn set(&mut self, row: i64, col: i64, state: CellState) {
+        match state {
+            CellState::Alive => self.live.insert((row, col)),
+            CellState::Dead => self.live.remove(&(row, col)),
+        };
+    }
+
+    /// Flips the cell at (row, col) from Alive->Dead or Dead->Alive.
+    pub fn toggle_cell(&mut self, row: i64, col: i64) {
+        if !self.live.remove(&(row, col)) {
+            self.live.insert((row, col));
+        }
+    }
+
+    /// Returns the number of live cells.
+    pub fn population(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Returns how many times `next_generation` has run.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Iterates over the (row, col) of every live cell, in no particular order.
+    pub fn live_cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.live.iter().copied()
+    }
+
+    /// Advances one generation under the standard B3/S23 rules.
+    ///
+    /// Only live cells and their neighbors can be alive next generation, so each
+    /// live cell adds one to the count of its 8 neighbors and the survivors and
+    /// births are read off those counts.
+    pub fn next_generation(&mut self) {
+        let mut neighbor_counts: HashMap<(i64, i64), u8> = HashMap::new();
+        for &(row, col) in &self.live {
+            for dr in -1..=1 {
+                for dc in -1..=1 {
+                    if (dr, dc) != (0, 0) {
+                        let neighbor = (row.wrapping_add(dr), col.wrapping_add(dc));
+                        *neighbor_counts.entry(neighbor).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        self.live = neighbor_counts
+            .into_iter()
+            .filter(|&(cell, count)| count == 3 || (count == 2 && self.live.contains(&cell)))
+            .map(|(cell, _)| cell)
+            .collect();
+        self.generation += 1;
+    }
+}
+
+/// Builds a board from the (row, col) of its live cells.
+impl FromIterator<(i64, i64)> for SparseGrid {
+    fn from_iter<I: IntoIterator<Item = (i64, i64)>>(iter: I) -> Self {
+        SparseGrid {
+            live: iter.into_iter().collect(),
+            generation: 0,
+        }
+    }
+}
+
+/// Copies the live cells of a dense grid, keeping their (row, col) positions.
+impl From<&Grid> for SparseGrid {
+    fn from(grid: &Grid) -> Self {
+        grid.live_cells()
+            .map(|(r, c)| (r as i64, c as i64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::NamedPattern;
+
+    const GLIDER: [(i64, i64); 5] = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+    fn sorted(grid: &SparseGrid) -> Vec<(i64, i64)> {
+        let mut cells: Vec<_> = grid.live_cells().collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn gliders_travel_through_negative_coordinates() {
+        // Starts far up and left of the origin and flies through it
+        let mut grid: SparseGrid = GLIDER.iter().map(|&(r, c)| (r - 20, c - 20)).collect();
+        for _ in 0..160 {
+            grid.next_generation();
+        }
+        // One cell diagonally every 4 generations
+        let expected: Vec<_> = GLIDER.iter().map(|&(r, c)| (r + 20, c + 20)).collect();
+        assert_eq!(sorted(&grid), expected);
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.generation(), 160);
+    }
+
+    #[test]
+    fn matches_a_dense_grid_away_from_its_edges() {
+        let mut dense = Grid::new(60, 60);
+        dense.stamp_pattern(NamedPattern::GosperGliderGun, 5, 5);
+        let mut sparse = SparseGrid::from(&dense);
+        for _ in 0..60 {
+            dense.next_generation();
+            sparse.next_generation();
+        }
+        let expected: Vec<_> = dense
+            .live_cells()
+            .map(|(r, c)| (r as i64, c as i64))
+            .collect();
+        assert_eq!(sorted(&sparse), expected);
+        // The gun has fired two gliders by now
+        assert_eq!(sparse.population(), 36 + 2 * 5);
+    }
+
+    #[test]
+    fn set_and_toggle_edit_single_cells() {
+        let mut grid = SparseGrid::new();
+        grid.set(-5, 7, CellState::Alive);
+        grid.toggle_cell(i64::MAX, i64::MIN);
+        assert_eq!(grid.get(-5, 7), CellState::Alive);
+        assert_eq!(grid.get(i64::MAX, i64::MIN), CellState::Alive);
+        assert_eq!(grid.population(), 2);
+        grid.toggle_cell(-5, 7);
+        grid.set(i64::MAX, i64::MIN, CellState::Dead);
+        assert_eq!(grid, SparseGrid::new());
+    }
+}
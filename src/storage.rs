@@ -0,0 +1,754 @@
+//! Backing storage for a `Grid`'s cells, addressed by flat row-major index.
+//!
+//! By default each cell takes one byte (`Vec<CellState>`). With the `bitpack`
+//! feature cells are packed 64 to a `u64`, cutting memory use by 8x on large boards.
+//! Both read and write cells through the same methods, so `Grid` doesn't care which
+//! one it's built with. They differ in how a generation is computed: the dense store
+//! is filled cell by cell, while the packed one steps itself, counting the neighbors
+//! of 64 cells at once with bitwise arithmetic. Either way the new generation's
+//! population and cell hash are totted up as it's written, so `Grid` doesn't have to
+//! scan the board again afterwards.
+//!
+//! Whichever of those is built in, a board can also switch at runtime to a sparse
+//! store holding only the indices of its live cells (see `Backend`), which `Grid`
+//! steps by visiting just the cells around them.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::grid::{Backend, CellState};
+
+#[cfg(not(feature = "bitpack"))]
+use dense::CellStore as DenseCells;
+#[cfg(feature = "bitpack")]
+use packed::CellStore as DenseCells;
+
+/// A board's cells, in one of the two `Backend`s. The methods reading and writing
+/// cells work the same on both.
+#[derive(Clone)]
+pub enum CellStore {
+    Dense(DenseCells),
+    Sparse(SparseCells),
+}
+
+impl Default for CellStore {
+    fn default() -> Self {
+        CellStore::Dense(DenseCells::default())
+    }
+}
+
+/// Equal when the same cells are alive, whichever backend each is in.
+impl PartialEq for CellStore {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CellStore::Dense(a), CellStore::Dense(b)) => a == b,
+            (CellStore::Sparse(a), CellStore::Sparse(b)) => a == b,
+            _ => {
+                self.cell_count() == other.cell_count()
+                    && self.alive_indices().eq(other.alive_indices())
+            }
+        }
+    }
+}
+
+impl CellStore {
+    /// A dense store of `len` dead cells.
+    pub fn new(len: usize) -> Self {
+        CellStore::Dense(DenseCells::new(len))
+    }
+
+    /// A store of `len` dead cells on the same backend as this one.
+    pub fn emptied(&self, len: usize) -> Self {
+        match self {
+            CellStore::Dense(_) => CellStore::new(len),
+            CellStore::Sparse(_) => CellStore::Sparse(SparseCells::new(len)),
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            CellStore::Dense(_) => Backend::Dense,
+            CellStore::Sparse(_) => Backend::Sparse,
+        }
+    }
+
+    /// The same cells moved to `backend`.
+    pub fn converted(&self, backend: Backend) -> Self {
+        let mut converted = match backend {
+            Backend::Dense => CellStore::new(self.cell_count()),
+            Backend::Sparse => CellStore::Sparse(SparseCells::new(self.cell_count())),
+        };
+        for index in self.alive_indices() {
+            converted.set(index, CellState::Alive);
+        }
+        converted
+    }
+
+    /// Number of cells, alive or dead.
+    pub fn cell_count(&self) -> usize {
+        match self {
+            CellStore::Dense(cells) => cells.cell_count(),
+            CellStore::Sparse(cells) => cells.len,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> CellState {
+        match self {
+            CellStore::Dense(cells) => cells.get(index),
+            CellStore::Sparse(cells) => cells.get(index),
+        }
+    }
+
+    pub fn set(&mut self, index: usize, state: CellState) {
+        match self {
+            CellStore::Dense(cells) => cells.set(index, state),
+            CellStore::Sparse(cells) => cells.set(index, state),
+        }
+    }
+
+    /// Heap memory taken by the cells, in bytes.
+    pub fn byte_size(&self) -> usize {
+        match self {
+            CellStore::Dense(cells) => cells.byte_size(),
+            CellStore::Sparse(cells) => cells.byte_size(),
+        }
+    }
+
+    /// Number of live cells with an index in `range`.
+    pub fn count_alive(&self, range: Range<usize>) -> usize {
+        match self {
+            CellStore::Dense(cells) => cells.count_alive(range),
+            CellStore::Sparse(cells) => cells.live.range(range).count(),
+        }
+    }
+
+    /// Indices of the live cells, in increasing order.
+    pub fn alive_indices(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            CellStore::Dense(cells) => Box::new(cells.alive_indices()),
+            CellStore::Sparse(cells) => Box::new(cells.live.iter().copied()),
+        }
+    }
+
+    /// Turns this store into a dense one for a new generation to be written into,
+    /// reusing its allocation if it's dense already.
+    fn dense_mut(&mut self) -> &mut DenseCells {
+        if let CellStore::Sparse(_) = self {
+            *self = CellStore::default();
+        }
+        match self {
+            CellStore::Dense(cells) => cells,
+            CellStore::Sparse(_) => unreachable!("replaced with a dense store above"),
+        }
+    }
+
+    /// Replaces the contents with `len` cells where cell `i` is `f(i)`, as a dense
+    /// store. Returns the XOR of `cell_key` over the new live cells and how many
+    /// there are.
+    #[cfg(not(feature = "bitpack"))]
+    pub fn fill_with(&mut self, len: usize, f: impl Fn(usize) -> CellState + Sync) -> (u64, usize) {
+        self.dense_mut().fill_with(len, f)
+    }
+
+    /// Writes the generation after this dense store into `out`, which becomes dense
+    /// too, as described for the packed store's own `step`. Sparse stores are stepped
+    /// by `Grid`, which knows which cells to visit.
+    #[cfg(feature = "bitpack")]
+    pub fn step(
+        &self,
+        out: &mut CellStore,
+        width: usize,
+        height: usize,
+        boundary: crate::grid::BoundaryMode,
+        neighborhood: crate::grid::Neighborhood,
+        rule: &dyn crate::rule::Rule,
+    ) -> (u64, usize) {
+        match self {
+            CellStore::Dense(cells) => {
+                cells.step(out.dense_mut(), width, height, boundary, neighborhood, rule)
+            }
+            CellStore::Sparse(_) => panic!("only dense cells step themselves"),
+        }
+    }
+
+    /// Empties the store into a sparse one of `len` dead cells, for a new generation
+    /// to be written into.
+    pub fn clear_sparse(&mut self, len: usize) {
+        match self {
+            CellStore::Sparse(cells) => {
+                cells.live.clear();
+                cells.len = len;
+            }
+            CellStore::Dense(_) => *self = CellStore::Sparse(SparseCells::new(len)),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn iter(&self) -> Box<dyn Iterator<Item = CellState> + '_> {
+        match self {
+            CellStore::Dense(cells) => Box::new(cells.iter()),
+            CellStore::Sparse(cells) => Box::new((0..cells.len).map(|index| cells.get(index))),
+        }
+    }
+}
+
+/// The indices of the live cells, kept in order. Memory grows with the population
+/// rather than the area, so a few gliders on a huge board cost next to nothing.
+#[derive(Clone, PartialEq, Default)]
+pub struct SparseCells {
+    live: BTreeSet<usize>,
+    len: usize,
+}
+
+impl SparseCells {
+    /// A store of `len` dead cells.
+    fn new(len: usize) -> Self {
+        SparseCells {
+            live: BTreeSet::new(),
+            len,
+        }
+    }
+
+    fn get(&self, index: usize) -> CellState {
+        assert!(index < self.len, "cell index out of bounds");
+        if self.live.contains(&index) {
+            CellState::Alive
+        } else {
+            CellState::Dead
+        }
+    }
+
+    fn set(&mut self, index: usize, state: CellState) {
+        assert!(index < self.len, "cell index out of bounds");
+        match state {
+            CellState::Alive => self.live.insert(index),
+            CellState::Dead => self.live.remove(&index),
+        };
+    }
+
+    /// Heap memory taken by the live cells' indices, in bytes. The tree's own
+    /// bookkeeping isn't counted, so this is a lower bound.
+    fn byte_size(&self) -> usize {
+        self.live.len() * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(not(feature = "bitpack"))]
+mod dense {
+    use std::ops::Range;
+
+    use crate::grid::{cell_key, CellState};
+
+    /// One `CellState` per cell.
+    #[derive(Clone, PartialEq, Default)]
+    pub struct CellStore(Vec<CellState>);
+
+    impl CellStore {
+        /// A store of `len` dead cells.
+        pub fn new(len: usize) -> Self {
+            CellStore(vec![CellState::Dead; len])
+        }
+
+        pub fn get(&self, index: usize) -> CellState {
+            self.0[index]
+        }
+
+        pub fn set(&mut self, index: usize, state: CellState) {
+            self.0[index] = state;
+        }
+
+        pub fn cell_count(&self) -> usize {
+            self.0.len()
+        }
+
+        /// Replaces the contents with `len` cells where cell `i` is `f(i)`, reusing
+        /// the existing allocation. Returns the XOR of `cell_key` over the new live
+        /// cells and how many there are.
+        #[cfg(not(feature = "rayon"))]
+        pub fn fill_with(
+            &mut self,
+            len: usize,
+            f: impl Fn(usize) -> CellState + Sync,
+        ) -> (u64, usize) {
+            let mut tally = (0, 0);
+            self.0.clear();
+            self.0.extend((0..len).map(|index| {
+                let state = f(index);
+                tally = add_cell(tally, index, state);
+                state
+            }));
+            tally
+        }
+
+        /// Parallel version of `fill_with`: cells are independent, so they're
+        /// computed on rayon's thread pool. Each chunk of work keeps its own tally,
+        /// and the tallies are combined at the end rather than shared between threads.
+        #[cfg(feature = "rayon")]
+        pub fn fill_with(
+            &mut self,
+            len: usize,
+            f: impl Fn(usize) -> CellState + Sync,
+        ) -> (u64, usize) {
+            use rayon::prelude::*;
+
+            self.0.clear();
+            self.0.resize(len, CellState::Dead);
+            self.0
+                .par_iter_mut()
+                .enumerate()
+                .fold(
+                    || (0, 0),
+                    |tally, (index, cell)| {
+                        *cell = f(index);
+                        add_cell(tally, index, *cell)
+                    },
+                )
+                .reduce(
+                    || (0, 0),
+                    |(hash, population), (more_hash, more)| (hash ^ more_hash, population + more),
+                )
+        }
+
+        /// Heap memory taken by the cells, in bytes.
+        pub fn byte_size(&self) -> usize {
+            std::mem::size_of_val(self.0.as_slice())
+        }
+
+        /// Number of live cells with an index in `range`.
+        pub fn count_alive(&self, range: Range<usize>) -> usize {
+            self.0[range]
+                .iter()
+                .filter(|&&state| state == CellState::Alive)
+                .count()
+        }
+
+        /// Indices of the live cells, in increasing order.
+        pub fn alive_indices(&self) -> impl Iterator<Item = usize> + '_ {
+            self.0
+                .iter()
+                .enumerate()
+                .filter(|(_, &state)| state == CellState::Alive)
+                .map(|(index, _)| index)
+        }
+
+        #[cfg(feature = "serde")]
+        pub fn iter(&self) -> impl Iterator<Item = CellState> + '_ {
+            self.0.iter().copied()
+        }
+    }
+
+    /// `tally` with the cell at `index` counted in, if it's alive.
+    fn add_cell((hash, population): (u64, usize), index: usize, state: CellState) -> (u64, usize) {
+        match state {
+            CellState::Alive => (hash ^ cell_key(index), population + 1),
+            CellState::Dead => (hash, population),
+        }
+    }
+}
+
+// Always built for tests, so its stepping can be checked against the cell-by-cell rule
+#[cfg(any(test, feature = "bitpack"))]
+#[cfg_attr(not(feature = "bitpack"), allow(dead_code))]
+mod packed {
+    use std::ops::Range;
+
+    use crate::grid::{cell_key, BoundaryMode, CellState, Neighborhood};
+    use crate::rule::Rule;
+
+    const BITS: usize = u64::BITS as usize;
+
+    /// One bit per cell (set = alive), 64 cells per word. Bits past `len` in the
+    /// last word are always zero, so the derived `PartialEq` compares cells exactly.
+    #[derive(Clone, PartialEq, Default)]
+    pub struct CellStore {
+        words: Vec<u64>,
+        len: usize,
+    }
+
+    impl CellStore {
+        /// A store of `len` dead cells.
+        pub fn new(len: usize) -> Self {
+            CellStore {
+                words: vec![0; len.div_ceil(BITS)],
+                len,
+            }
+        }
+
+        pub fn get(&self, index: usize) -> CellState {
+            assert!(index < self.len, "cell index out of bounds");
+            match (self.words[index / BITS] >> (index % BITS)) & 1 {
+                1 => CellState::Alive,
+                _ => CellState::Dead,
+            }
+        }
+
+        pub fn set(&mut self, index: usize, state: CellState) {
+            assert!(index < self.len, "cell index out of bounds");
+            let mask = 1 << (index % BITS);
+            match state {
+                CellState::Alive => self.words[index / BITS] |= mask,
+                CellState::Dead => self.words[index / BITS] &= !mask,
+            }
+        }
+
+        pub fn cell_count(&self) -> usize {
+            self.len
+        }
+
+        fn resize_words(&mut self, len: usize) {
+            self.len = len;
+            self.words.clear();
+            self.words.resize(len.div_ceil(BITS), 0);
+        }
+
+        /// Heap memory taken by the cells, in bytes.
+        pub fn byte_size(&self) -> usize {
+            std::mem::size_of_val(self.words.as_slice())
+        }
+
+        /// Number of live cells with an index in `range`, counted a word at a time.
+        pub fn count_alive(&self, range: Range<usize>) -> usize {
+            let mut count = 0;
+            let mut index = range.start;
+            while index < range.end {
+                let offset = index % BITS;
+                let bits = (BITS - offset).min(range.end - index);
+                let mask = if bits == BITS {
+                    u64::MAX
+                } else {
+                    ((1 << bits) - 1) << offset
+                };
+                count += (self.words[index / BITS] & mask).count_ones() as usize;
+                index += bits;
+            }
+            count
+        }
+
+        /// Indices of the live cells, in increasing order.
+        pub fn alive_indices(&self) -> impl Iterator<Item = usize> + '_ {
+            self.words
+                .iter()
+                .enumerate()
+                .flat_map(|(word_index, &word)| {
+                    // Peel off the lowest set bit until the word is empty
+                    std::iter::successors(Some(word), |&bits| Some(bits & bits.wrapping_sub(1)))
+                        .take_while(|&bits| bits != 0)
+                        .map(move |bits| word_index * BITS + bits.trailing_zeros() as usize)
+                })
+        }
+
+        #[cfg(feature = "serde")]
+        pub fn iter(&self) -> impl Iterator<Item = CellState> + '_ {
+            (0..self.len).map(|index| self.get(index))
+        }
+
+        /// Writes the generation after this one into `out`, for a `width` x `height`
+        /// board. Gives the same result as applying `rule` cell by cell, but works on
+        /// up to 64 cells of a row at a time: the neighbors of every cell in the run
+        /// are lined up as eight words, which are summed into a 4-bit count per cell
+        /// with bitwise adders.
+        ///
+        /// Returns the XOR of `cell_key` over the live cells written and how many
+        /// there are, counted a run at a time.
+        pub fn step(
+            &self,
+            out: &mut CellStore,
+            width: usize,
+            height: usize,
+            boundary: BoundaryMode,
+            neighborhood: Neighborhood,
+            rule: &dyn Rule,
+        ) -> (u64, usize) {
+            let board = Board {
+                cells: self,
+                width,
+                height,
+                boundary,
+            };
+            // All ones in the word for neighbor count `n` if `rule` keeps a cell alive
+            let table = |current: CellState| -> [u64; 9] {
+                std::array::from_fn(|n| match rule.next_state(current, n) {
+                    CellState::Alive => u64::MAX,
+                    CellState::Dead => 0,
+                })
+            };
+            let (birth, survival) = (table(CellState::Dead), table(CellState::Alive));
+            let step_run = |row: usize, col: usize| {
+                let len = (width - col).min(BITS);
+                let above = board.run(row as isize - 1, col, len);
+                let here = board.run(row as isize, col, len);
+                let below = board.run(row as isize + 1, col, len);
+                let neighbors = match neighborhood {
+                    Neighborhood::Moore => vec![
+                        above.west,
+                        above.cells,
+                        above.east,
+                        here.west,
+                        here.east,
+                        below.west,
+                        below.cells,
+                        below.east,
+                    ],
+                    Neighborhood::VonNeumann => {
+                        vec![above.cells, here.west, here.east, below.cells]
+                    }
+                };
+                // Bit `i` of `count[k]` is bit `k` of cell `i`'s neighbor count
+                let mut count = [0u64; 4];
+                for word in neighbors {
+                    let mut carry = word;
+                    for bit in &mut count {
+                        (*bit, carry) = (*bit ^ carry, *bit & carry);
+                    }
+                }
+                let next = (0..=8).fold(0, |next, n: usize| {
+                    let matches = count.iter().enumerate().fold(u64::MAX, |mask, (k, &bit)| {
+                        mask & if n >> k & 1 == 1 { bit } else { !bit }
+                    });
+                    let alive = (here.cells & survival[n]) | (!here.cells & birth[n]);
+                    next | (matches & alive)
+                });
+                (row * width + col, len, next & low_bits(len))
+            };
+            let runs = |row: usize| (0..width).step_by(BITS).map(move |col| step_run(row, col));
+
+            out.resize_words(self.len);
+            #[cfg(not(feature = "rayon"))]
+            {
+                let mut tally = (0, 0);
+                for (index, len, bits) in (0..height).flat_map(runs) {
+                    out.write_bits(index, len, bits);
+                    tally = add_run(tally, index, bits);
+                }
+                tally
+            }
+            // Runs can share a word with the next row's, so they're computed in
+            // parallel but written one at a time. Each row is tallied on its own
+            // thread and only the per-row totals are summed here.
+            #[cfg(feature = "rayon")]
+            {
+                use rayon::prelude::*;
+
+                let rows: Vec<(Vec<_>, _)> = (0..height)
+                    .into_par_iter()
+                    .map(|row| {
+                        let row_runs: Vec<_> = runs(row).collect();
+                        let tally = row_runs.iter().fold((0, 0), |tally, &(index, _, bits)| {
+                            add_run(tally, index, bits)
+                        });
+                        (row_runs, tally)
+                    })
+                    .collect();
+                let mut tally = (0, 0);
+                for (row_runs, (row_hash, row_population)) in rows {
+                    for (index, len, bits) in row_runs {
+                        out.write_bits(index, len, bits);
+                    }
+                    tally = (tally.0 ^ row_hash, tally.1 + row_population);
+                }
+                tally
+            }
+        }
+
+        /// `len` (at most 64) cells starting at `index`, as the low bits of a word.
+        fn read_bits(&self, index: usize, len: usize) -> u64 {
+            let (word, offset) = (index / BITS, index % BITS);
+            let mut bits = self.words[word] >> offset;
+            if offset > 0 && offset + len > BITS {
+                bits |= self.words[word + 1] << (BITS - offset);
+            }
+            bits & low_bits(len)
+        }
+
+        /// Overwrites `len` (at most 64) cells starting at `index` with the low bits
+        /// of `bits`, which must be clear above `len`.
+        fn write_bits(&mut self, index: usize, len: usize, bits: u64) {
+            let (word, offset) = (index / BITS, index % BITS);
+            let mask = low_bits(len);
+            self.words[word] = (self.words[word] & !(mask << offset)) | bits << offset;
+            if offset > 0 && offset + len > BITS {
+                let shift = BITS - offset;
+                self.words[word + 1] = (self.words[word + 1] & !(mask >> shift)) | bits >> shift;
+            }
+        }
+    }
+
+    /// `tally` with the live cells of a run counted in: bit `i` of `bits` is the
+    /// cell at `index + i`.
+    fn add_run((mut hash, population): (u64, usize), index: usize, bits: u64) -> (u64, usize) {
+        let mut rest = bits;
+        while rest != 0 {
+            hash ^= cell_key(index + rest.trailing_zeros() as usize);
+            rest &= rest - 1;
+        }
+        (hash, population + bits.count_ones() as usize)
+    }
+
+    /// A word with the low `len` bits set.
+    fn low_bits(len: usize) -> u64 {
+        if len == BITS {
+            u64::MAX
+        } else {
+            (1 << len) - 1
+        }
+    }
+
+    /// A store read as a board, with neighbors off the edge resolved like
+    /// `Grid::count_live_neighbors` does.
+    struct Board<'a> {
+        cells: &'a CellStore,
+        width: usize,
+        height: usize,
+        boundary: BoundaryMode,
+    }
+
+    /// A run of cells in a row, along with the same run shifted one cell either way:
+    /// bit `i` of `west` is the cell to the west of bit `i` of `cells`.
+    struct Run {
+        west: u64,
+        cells: u64,
+        east: u64,
+    }
+
+    impl Board<'_> {
+        /// The `len` cells from column `col` of row `row`, which may be one past
+        /// either edge.
+        fn run(&self, row: isize, col: usize, len: usize) -> Run {
+            let Some(row) = self.resolve(row, self.height) else {
+                let edge = self.edge();
+                return Run {
+                    west: edge & low_bits(len),
+                    cells: edge & low_bits(len),
+                    east: edge & low_bits(len),
+                };
+            };
+            let start = row * self.width;
+            let cell = |col: isize| match self.resolve(col, self.width) {
+                Some(col) => self.cells.read_bits(start + col, 1),
+                None => self.edge() & 1,
+            };
+            let cells = self.cells.read_bits(start + col, len);
+            Run {
+                west: (cells << 1 | cell(col as isize - 1)) & low_bits(len),
+                cells,
+                east: cells >> 1 | cell((col + len) as isize) << (len - 1),
+            }
+        }
+
+        /// The in-bounds row or column standing in for `index` on an axis of
+        /// length `len`, or None if it's off the edge with no stand-in.
+        fn resolve(&self, index: isize, len: usize) -> Option<usize> {
+            let len = len as isize;
+            if (0..len).contains(&index) {
+                return Some(index as usize);
+            }
+            match self.boundary {
+                BoundaryMode::Dead | BoundaryMode::Alive => None,
+                BoundaryMode::Mirror => Some(index.clamp(0, len - 1) as usize),
+                BoundaryMode::Wrap => Some(index.rem_euclid(len) as usize),
+            }
+        }
+
+        /// Every cell off the edge, for boundary modes without a stand-in.
+        fn edge(&self) -> u64 {
+            match self.boundary {
+                BoundaryMode::Alive => u64::MAX,
+                _ => 0,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CellStore {
+    /// Serialized as a plain sequence of `CellState`s whichever backend is in use.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{BoundaryMode, CellState, Grid, Neighborhood};
+    use crate::patterns::NamedPattern;
+    use crate::rule::LifelikeRule;
+
+    const BOUNDARY_MODES: [BoundaryMode; 4] = [
+        BoundaryMode::Dead,
+        BoundaryMode::Alive,
+        BoundaryMode::Mirror,
+        BoundaryMode::Wrap,
+    ];
+
+    /// Boards to step: runs that span words, end mid-word, and sit on every edge.
+    fn boards() -> Vec<(&'static str, Grid)> {
+        let mut glider = Grid::new(70, 9);
+        glider.load_pattern(NamedPattern::Glider.cells(), 0, 62);
+        let mut pulsar = Grid::new(17, 17);
+        pulsar.load_pattern(NamedPattern::Pulsar.cells(), 2, 2);
+        let mut soup = Grid::new(130, 40);
+        soup.randomize(0.4, 7);
+        let mut highlife = soup.clone();
+        highlife.set_rule("B36/S23".parse::<LifelikeRule>().unwrap());
+        vec![
+            ("glider", glider),
+            ("pulsar", pulsar),
+            ("soup", soup),
+            ("highlife", highlife),
+        ]
+    }
+
+    fn packed_cells(grid: &Grid) -> super::packed::CellStore {
+        let mut cells = super::packed::CellStore::new(grid.width * grid.height);
+        for (row, col) in grid.live_cells() {
+            cells.set(row * grid.width + col, CellState::Alive);
+        }
+        cells
+    }
+
+    /// Steps every board under every boundary mode and neighborhood for several
+    /// generations, both with the packed store and with `Grid` (on whichever backend
+    /// it's built with), checking each against the rule applied cell by cell.
+    #[test]
+    fn packed_steps_match_the_dense_grid() {
+        for (name, board) in boards() {
+            for boundary in BOUNDARY_MODES {
+                for neighborhood in [Neighborhood::Moore, Neighborhood::VonNeumann] {
+                    let mut grid = board.clone();
+                    grid.set_boundary_mode(boundary);
+                    grid.set_neighborhood(neighborhood);
+                    let mut cells = packed_cells(&grid);
+                    let mut next = super::packed::CellStore::default();
+                    for generation in 1..=8 {
+                        let (width, height) = (grid.width, grid.height);
+                        let (_, population) = cells.step(
+                            &mut next,
+                            width,
+                            height,
+                            boundary,
+                            neighborhood,
+                            grid.rule(),
+                        );
+                        std::mem::swap(&mut cells, &mut next);
+                        // `preview_next` applies the rule one cell at a time
+                        let expected = grid.preview_next();
+                        grid.next_generation();
+                        let context =
+                            format!("{name}, {boundary:?}, {neighborhood:?}, gen {generation}");
+                        assert!(
+                            (0..width * height).all(|index| cells.get(index) == expected[index]),
+                            "packed store differs: {context}"
+                        );
+                        assert!(
+                            (0..width * height)
+                                .all(|index| grid.get_copy(index / width, index % width)
+                                    == Some(expected[index])),
+                            "grid differs: {context}"
+                        );
+                        assert_eq!(population, grid.population(), "{context}");
+                    }
+                }
+            }
+        }
+    }
+}